@@ -0,0 +1,301 @@
+//! Decoder for the two phase shifted signals (A and B) of rotary encoders.
+//!
+//! Turning the encoder walks through the four states of the signals in gray
+//! code order, one direction per order:
+//!
+//! ```text
+//!           ┌───────┐       ┌───
+//! A    ─────┘       └───────┘
+//!               ┌───────┐
+//! B    ─────────┘       └───────
+//!
+//! state  00  10  11  01  00  10
+//! ```
+//!
+//! The [`Decoder`] counts every valid transition and reports a step once the
+//! count reaches the next detent of the encoder. Contact bounce walks back and
+//! forth between two neighbouring states, so it can never reach another
+//! detent on its own.
+#![no_std]
+
+#[cfg(feature = "mock")]
+extern crate alloc;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+/// Direction of a step of the encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Direction {
+    /// Returns the opposite direction.
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Clockwise => Self::CounterClockwise,
+            Self::CounterClockwise => Self::Clockwise,
+        }
+    }
+}
+
+/// Number of signal transitions between two detents of the encoder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum StepMode {
+    /// One step per full cycle of four transitions, the most common type with
+    /// one detent per pulse
+    #[default]
+    Full,
+    /// One step per two transitions, for encoders with two detents per pulse
+    Half,
+    /// One step per transition, for encoders without detents
+    Quarter,
+}
+
+impl StepMode {
+    fn transitions(self) -> i32 {
+        match self {
+            Self::Full => 4,
+            Self::Half => 2,
+            Self::Quarter => 1,
+        }
+    }
+}
+
+/// Change of the count for a transition from the previous to the current
+/// state, indexed by `previous << 2 | current`. Invalid transitions, where
+/// both signals changed at once, are ignored.
+const TRANSITIONS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+/// Quadrature decoding state machine.
+///
+/// The levels at creation are taken as detent position, use
+/// [`Decoder::with_levels`] if the encoder does not rest with both signals
+/// low.
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    mode: StepMode,
+    inverted: bool,
+    state: u8,
+    /// Transitions counted since the start
+    count: i32,
+    /// Count at the detent of the last reported step
+    detent: i32,
+}
+
+impl Decoder {
+    pub fn new(mode: StepMode) -> Self {
+        Self {
+            mode,
+            inverted: false,
+            state: 0,
+            count: 0,
+            detent: 0,
+        }
+    }
+
+    /// Sets the levels of the signals at the current detent.
+    pub fn with_levels(mut self, a: bool, b: bool) -> Self {
+        self.state = state(a, b);
+        self
+    }
+
+    /// Reverses the reported direction, for encoders with swapped signals.
+    pub fn inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Changes the reported direction, e.g. after the setting changed.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
+    pub fn mode(&self) -> StepMode {
+        self.mode
+    }
+
+    /// Updates the decoder with the current levels of the signals and
+    /// returns the step if the encoder reached the next detent.
+    pub fn update(&mut self, a: bool, b: bool) -> Option<Direction> {
+        let current = state(a, b);
+        if current == self.state {
+            return None;
+        }
+
+        let change = TRANSITIONS[(self.state << 2 | current) as usize];
+        self.state = current;
+        self.count = self.count.wrapping_add(change as i32);
+
+        let transitions = self.mode.transitions();
+        let direction = match self.count.wrapping_sub(self.detent) {
+            moved if moved >= transitions => Direction::Clockwise,
+            moved if moved <= -transitions => Direction::CounterClockwise,
+            _ => return None,
+        };
+        self.detent = match direction {
+            Direction::Clockwise => self.detent.wrapping_add(transitions),
+            Direction::CounterClockwise => self.detent.wrapping_sub(transitions),
+        };
+
+        Some(if self.inverted {
+            direction.reversed()
+        } else {
+            direction
+        })
+    }
+}
+
+fn state(a: bool, b: bool) -> u8 {
+    (a as u8) << 1 | b as u8
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use proptest::prelude::*;
+    use std::vec::Vec;
+
+    /// Signal levels for one cycle clockwise, starting after the rest state
+    const CYCLE: [(bool, bool); 4] = [(true, false), (true, true), (false, true), (false, false)];
+
+    /// Levels of the signals for `steps` detents, positive steps turn
+    /// clockwise. Each transition is repeated `bounces[i]` times as bounce
+    /// back to the previous state and forth again.
+    fn waveform(mode: StepMode, steps: &[i8], bounces: &[u8]) -> Vec<(bool, bool)> {
+        let mut position: i32 = 0;
+        let mut levels = Vec::new();
+        let mut bounces = bounces.iter().cycle();
+
+        for step in steps {
+            let direction = step.signum() as i32;
+            for _ in 0..step.unsigned_abs() as i32 * mode.transitions() {
+                let previous = CYCLE[(position - 1).rem_euclid(4) as usize];
+                position += direction;
+                let next = CYCLE[(position - 1).rem_euclid(4) as usize];
+
+                levels.push(next);
+                for _ in 0..*bounces.next().unwrap_or(&0) {
+                    levels.push(previous);
+                    levels.push(next);
+                }
+            }
+        }
+        levels
+    }
+
+    fn decode(decoder: &mut Decoder, levels: &[(bool, bool)]) -> Vec<Direction> {
+        levels
+            .iter()
+            .filter_map(|(a, b)| decoder.update(*a, *b))
+            .collect()
+    }
+
+    fn expected(steps: &[i8]) -> Vec<Direction> {
+        steps
+            .iter()
+            .flat_map(|step| {
+                let direction = if *step > 0 {
+                    Direction::Clockwise
+                } else {
+                    Direction::CounterClockwise
+                };
+                core::iter::repeat_n(direction, step.unsigned_abs() as usize)
+            })
+            .collect()
+    }
+
+    fn step_mode() -> impl Strategy<Value = StepMode> {
+        prop_oneof![
+            Just(StepMode::Full),
+            Just(StepMode::Half),
+            Just(StepMode::Quarter)
+        ]
+    }
+
+    #[test]
+    fn test_full_step_needs_whole_cycle() {
+        let mut decoder = Decoder::new(StepMode::Full);
+
+        assert_eq!(decode(&mut decoder, &CYCLE[..3]), []);
+        assert_eq!(decode(&mut decoder, &CYCLE[3..]), [Direction::Clockwise]);
+    }
+
+    #[test]
+    fn test_half_step_reports_at_both_rest_states() {
+        let mut decoder = Decoder::new(StepMode::Half);
+
+        assert_eq!(
+            decode(&mut decoder, &CYCLE),
+            [Direction::Clockwise, Direction::Clockwise]
+        );
+    }
+
+    #[test]
+    fn test_inverted() {
+        let mut decoder = Decoder::new(StepMode::Full).inverted(true);
+
+        assert_eq!(decode(&mut decoder, &CYCLE), [Direction::CounterClockwise]);
+        decoder.set_inverted(false);
+        assert_eq!(decode(&mut decoder, &CYCLE), [Direction::Clockwise]);
+    }
+
+    #[test]
+    fn test_rest_state_with_both_signals_high() {
+        let mut decoder = Decoder::new(StepMode::Full).with_levels(true, true);
+        let levels = [(false, true), (false, false), (true, false), (true, true)];
+
+        assert_eq!(decode(&mut decoder, &levels), [Direction::Clockwise]);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_clean_waveform_decodes_every_step(
+            mode in step_mode(),
+            steps in prop::collection::vec(prop_oneof![-5i8..=-1, 1i8..=5], 0..20),
+        ) {
+            let mut decoder = Decoder::new(mode);
+            let levels = waveform(mode, &steps, &[]);
+
+            prop_assert_eq!(decode(&mut decoder, &levels), expected(&steps));
+        }
+
+        #[test]
+        fn prop_bounce_adds_no_steps(
+            mode in prop_oneof![Just(StepMode::Full), Just(StepMode::Half)],
+            steps in prop::collection::vec(prop_oneof![-5i8..=-1, 1i8..=5], 0..20),
+            bounces in prop::collection::vec(0u8..4, 1..16),
+        ) {
+            let mut decoder = Decoder::new(mode);
+            let levels = waveform(mode, &steps, &bounces);
+
+            prop_assert_eq!(decode(&mut decoder, &levels), expected(&steps));
+        }
+
+        /// Without detents every transition is a step, bounce shows up as
+        /// steps which cancel each other out.
+        #[test]
+        fn prop_quarter_step_bounce_cancels_out(
+            steps in prop::collection::vec(prop_oneof![-5i8..=-1, 1i8..=5], 0..20),
+            bounces in prop::collection::vec(0u8..4, 1..16),
+        ) {
+            let mut decoder = Decoder::new(StepMode::Quarter);
+            let levels = waveform(StepMode::Quarter, &steps, &bounces);
+
+            let net: i32 = decode(&mut decoder, &levels)
+                .iter()
+                .map(|direction| match direction {
+                    Direction::Clockwise => 1,
+                    Direction::CounterClockwise => -1,
+                })
+                .sum();
+            prop_assert_eq!(net, steps.iter().map(|step| *step as i32).sum::<i32>());
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Simulated input pins, to test encoder and button inputs without hardware.
+//!
+//! A [`ScriptedPin`] replays a [`Script`] of timed levels. All pins of a test
+//! share a [`MockClock`], which the test advances between two polls of the
+//! input, so the result does not depend on the speed of the machine.
+//!
+//! ```
+//! use core::time::Duration;
+//! use embedded_hal::digital::InputPin;
+//! use quadrature::{mock::{MockClock, Script, ScriptedPin}};
+//!
+//! let clock = MockClock::new();
+//! let script = Script::new(true).then(Duration::from_millis(10), false);
+//! let mut button = ScriptedPin::new(script, &clock);
+//!
+//! assert!(button.is_high().unwrap());
+//! clock.advance(Duration::from_millis(10));
+//! assert!(button.is_low().unwrap());
+//! ```
+
+use alloc::{rc::Rc, vec::Vec};
+use core::{cell::Cell, convert::Infallible, time::Duration};
+
+use embedded_hal::digital::{ErrorType, InputPin};
+
+use crate::StepMode;
+
+/// Simulated time, shared by all pins created with it.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock(Rc<Cell<Duration>>);
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time since the start of the scripts.
+    pub fn now(&self) -> Duration {
+        self.0.get()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+/// Levels of a pin over time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    initial: bool,
+    /// Level changes, ordered by time
+    changes: Vec<(Duration, bool)>,
+}
+
+impl Script {
+    /// Creates a script holding `initial` until the first change.
+    pub fn new(initial: bool) -> Self {
+        Self {
+            initial,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Changes the level to `level` at `at`. Changes have to be added in
+    /// chronological order.
+    pub fn then(mut self, at: Duration, level: bool) -> Self {
+        self.push(at, level);
+        self
+    }
+
+    fn push(&mut self, at: Duration, level: bool) {
+        debug_assert!(self.changes.last().is_none_or(|(last, _)| *last <= at));
+        self.changes.push((at, level));
+    }
+
+    /// Level of the pin at `time`.
+    pub fn level_at(&self, time: Duration) -> bool {
+        self.changes
+            .iter()
+            .take_while(|(at, _)| *at <= time)
+            .last()
+            .map_or(self.initial, |(_, level)| *level)
+    }
+
+    /// Time of the last change.
+    pub fn end(&self) -> Duration {
+        self.changes.last().map_or(Duration::ZERO, |(at, _)| *at)
+    }
+}
+
+/// [`InputPin`] reading its level from a [`Script`] at the time of a
+/// [`MockClock`].
+#[derive(Debug, Clone)]
+pub struct ScriptedPin {
+    script: Script,
+    clock: MockClock,
+}
+
+impl ScriptedPin {
+    pub fn new(script: Script, clock: &MockClock) -> Self {
+        Self {
+            script,
+            clock: clock.clone(),
+        }
+    }
+}
+
+impl ErrorType for ScriptedPin {
+    type Error = Infallible;
+}
+
+impl InputPin for ScriptedPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.script.level_at(self.clock.now()))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+/// Levels of the signals in clockwise order, starting after the rest state
+/// with both signals low.
+const CYCLE: [(bool, bool); 4] = [(true, false), (true, true), (false, true), (false, false)];
+
+/// Generator for the A and B signals of a turning encoder.
+#[derive(Debug, Clone)]
+pub struct Waveform {
+    mode: StepMode,
+    start: Duration,
+    transition: Duration,
+    bounces: u8,
+    bounce: Duration,
+}
+
+impl Waveform {
+    /// Creates a waveform for an encoder resting with both signals low, with
+    /// 1 ms between two transitions and without bounce.
+    pub fn new(mode: StepMode) -> Self {
+        Self {
+            mode,
+            start: Duration::ZERO,
+            transition: Duration::from_millis(1),
+            bounces: 0,
+            bounce: Duration::ZERO,
+        }
+    }
+
+    /// Time of the first transition.
+    pub fn starting_at(mut self, start: Duration) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Time between two transitions, turning faster makes it shorter.
+    pub fn with_transition_time(mut self, transition: Duration) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// Lets each changing signal bounce back `bounces` times, `duration`
+    /// apart, before it settles. The bounces are squeezed into the time until
+    /// the next transition if they would last longer.
+    pub fn with_bounce(mut self, bounces: u8, duration: Duration) -> Self {
+        self.bounces = bounces;
+        self.bounce = duration;
+        self
+    }
+
+    /// Creates the scripts for the A and B signals turning the encoder by
+    /// `steps` detents, positive steps turn clockwise.
+    pub fn scripts(&self, steps: &[i32]) -> (Script, Script) {
+        let (mut a, mut b) = (Script::new(false), Script::new(false));
+        let transitions = match self.mode {
+            StepMode::Full => 4,
+            StepMode::Half => 2,
+            StepMode::Quarter => 1,
+        };
+
+        // the signal has to settle before it changes again
+        let bounce = match self.bounces {
+            0 => self.bounce,
+            bounces => self.bounce.min(self.transition / (2 * u32::from(bounces))),
+        };
+
+        let mut position: i32 = 0;
+        let mut time = self.start;
+        for step in steps {
+            for _ in 0..step.unsigned_abs() * transitions {
+                let previous = CYCLE[(position - 1).rem_euclid(4) as usize];
+                position += step.signum();
+                let next = CYCLE[(position - 1).rem_euclid(4) as usize];
+
+                let (script, level) = if previous.0 != next.0 {
+                    (&mut a, next.0)
+                } else {
+                    (&mut b, next.1)
+                };
+                script.push(time, level);
+                let mut bounce_time = time;
+                for _ in 0..self.bounces {
+                    script.push(bounce_time + bounce, !level);
+                    script.push(bounce_time + 2 * bounce, level);
+                    bounce_time += 2 * bounce;
+                }
+
+                time += self.transition;
+            }
+        }
+        (a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decoder, Direction};
+
+    fn sample(mode: StepMode, (a, b): (Script, Script), interval: Duration) -> Vec<Direction> {
+        let clock = MockClock::new();
+        let end = a.end().max(b.end());
+        let (mut a, mut b) = (ScriptedPin::new(a, &clock), ScriptedPin::new(b, &clock));
+        let mut decoder = Decoder::new(mode);
+
+        let mut steps = Vec::new();
+        while clock.now() <= end + interval {
+            if let Some(step) = decoder.update(a.is_high().unwrap(), b.is_high().unwrap()) {
+                steps.push(step);
+            }
+            clock.advance(interval);
+        }
+        steps
+    }
+
+    #[test]
+    fn test_script_levels() {
+        let script = Script::new(false)
+            .then(Duration::from_millis(5), true)
+            .then(Duration::from_millis(8), false);
+
+        assert!(!script.level_at(Duration::from_millis(4)));
+        assert!(script.level_at(Duration::from_millis(5)));
+        assert!(!script.level_at(Duration::from_millis(9)));
+    }
+
+    #[test]
+    fn test_bouncy_waveform_sampled_by_decoder() {
+        let waveform = Waveform::new(StepMode::Full)
+            .with_transition_time(Duration::from_millis(2))
+            .with_bounce(2, Duration::from_micros(100));
+        let scripts = waveform.scripts(&[2, -1]);
+
+        assert_eq!(
+            sample(StepMode::Full, scripts, Duration::from_micros(50)),
+            [
+                Direction::Clockwise,
+                Direction::Clockwise,
+                Direction::CounterClockwise
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_bounce_settles_before_next_transition() {
+        let waveform = Waveform::new(StepMode::Full)
+            .with_transition_time(Duration::from_millis(1))
+            .with_bounce(3, Duration::from_millis(5));
+        let (a, b) = waveform.scripts(&[1]);
+
+        for script in [&a, &b] {
+            assert!(script.changes.is_sorted_by_key(|(at, _)| *at));
+        }
+        assert!(a.end().max(b.end()) <= Duration::from_millis(4));
+    }
+
+    #[test]
+    fn test_sampling_too_slow_misses_steps() {
+        // two transitions between two samples look like an invalid transition
+        let waveform = Waveform::new(StepMode::Full).with_transition_time(Duration::from_millis(1));
+        let scripts = waveform.scripts(&[3]);
+
+        assert!(sample(StepMode::Full, scripts, Duration::from_millis(2)).len() < 3);
+    }
+}
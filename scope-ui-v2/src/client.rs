@@ -1,19 +1,30 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, bail};
+use log::{debug, error, info};
 
-pub struct AppConfig {
-    pub openflexure_url: url::Url,
-    pub phoenix_url: url::Url,
-}
+use crate::{config::AppConfig, limits::SoftLimits, units::StepsPerPixel};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct OpenFlexurePosition {
     pub x: i64,
     pub y: i64,
     pub z: i64,
 }
 
+impl OpenFlexurePosition {
+    pub fn axis(&self, axis: OpenflexureAxis) -> i64 {
+        match axis {
+            OpenflexureAxis::X => self.x,
+            OpenflexureAxis::Y => self.y,
+            OpenflexureAxis::Z => self.z,
+        }
+    }
+}
+
 #[derive(serde::Serialize)]
 struct MoveStageRequest<'a> {
     direction: &'a str,
@@ -25,40 +36,57 @@ impl TryFrom<serde_json::Value> for OpenFlexurePosition {
 
     fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
         let pos = (value.get("x"), value.get("y"), value.get("z"));
-        if let (Some(x), Some(y), Some(z)) = pos {
-            if let (Some(x), Some(y), Some(z)) = (x.as_i64(), y.as_i64(), z.as_i64()) {
-                return Ok(Self { x, y, z });
-            }
+        if let (Some(x), Some(y), Some(z)) = pos
+            && let (Some(x), Some(y), Some(z)) = (x.as_i64(), y.as_i64(), z.as_i64())
+        {
+            return Ok(Self { x, y, z });
         }
 
         bail!("Failed to parse openflexure positon from input")
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OpenflexureAxis {
     X,
     Y,
     Z,
 }
 
+impl OpenflexureAxis {
+    pub const ALL: [Self; 3] = [Self::X, Self::Y, Self::Z];
+}
+
 #[derive(Debug)]
 pub enum MoveDirection {
     Pos(OpenflexureAxis),
     Neg(OpenflexureAxis),
 }
 
+/// Time to wait for a server to answer a ping
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest time to wait for a stage move to finish
+const ACTION_TIMEOUT: Duration = Duration::from_secs(60);
+const ACTION_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Longest time to wait for the camera stage mapping calibration, which
+/// moves the stage many times
+const CALIBRATION_TIMEOUT: Duration = Duration::from_secs(600);
+/// Path of the OpenFlexure extension relating stage moves to image shifts
+const CAMERA_STAGE_MAPPING: &str = "api/v2/extensions/org.openflexure.camera_stage_mapping";
+
 #[derive(Clone)]
 pub struct AppClient {
     openflexure_url: url::Url,
     phoenix_url: url::Url,
+    limits: SoftLimits,
 }
 
 impl AppClient {
     pub fn new(config: &AppConfig) -> Self {
         Self {
             openflexure_url: config.openflexure_url.clone(),
-            phoenix_url: config.openflexure_url.clone(),
+            phoenix_url: config.phoenix_url.clone(),
+            limits: config.limits.clone(),
         }
     }
 
@@ -77,34 +105,43 @@ impl AppClient {
             .await
             .context("Failed to parse response to json")?;
 
-        json.try_into()
+        let position: OpenFlexurePosition = json.try_into()?;
+        debug!("openflexure position {:?}", position);
+        Ok(position)
     }
 
     pub async fn move_openflexure(
         &self,
         direction: MoveDirection,
+        step_size: i64,
     ) -> anyhow::Result<reqwest::Response> {
         match direction {
-            MoveDirection::Pos(axis) => self.move_axis(axis, 200),
-            MoveDirection::Neg(axis) => self.move_axis(axis, -200),
+            MoveDirection::Pos(axis) => self.move_axis(axis, step_size),
+            MoveDirection::Neg(axis) => self.move_axis(axis, -step_size),
         }
         .await
     }
 
-    pub async fn move_slider(&self, up: bool) -> anyhow::Result<reqwest::Response> {
-        let url = "http://localhost:4000/api/move/slider";
+    /// Moves the slider by `step_size` steps. The soft limits are not
+    /// checked, Phoenix keeps the slider within its boundaries.
+    pub async fn move_slider(&self, up: bool, step_size: i64) -> anyhow::Result<reqwest::Response> {
+        let url = self.phoenix_url.join("api/move/slider")?;
         let direction = if up { "left" } else { "right" };
         let body = MoveStageRequest {
             direction,
-            step_size: 200,
+            step_size,
         };
-        reqwest::Client::new()
+        info!("move slider {}", direction);
+        let response = reqwest::Client::new()
             .post(url)
             .header("content-type", "application/json")
             .json(&body)
             .send()
             .await
-            .context("Failed to post move axis request")
+            .context("Failed to post move axis request")?;
+
+        log_response("slider", &response);
+        Ok(response)
     }
 
     async fn move_axis(
@@ -112,34 +149,228 @@ impl AppClient {
         axis: OpenflexureAxis,
         value: i64,
     ) -> anyhow::Result<reqwest::Response> {
+        info!("move {:?} by {}", axis, value);
+        match axis {
+            OpenflexureAxis::X => self.move_by(value, 0, 0),
+            OpenflexureAxis::Y => self.move_by(0, value, 0),
+            OpenflexureAxis::Z => self.move_by(0, 0, value),
+        }
+        .await
+    }
+
+    /// Moves the stage by `x` and `y` in one move, used for jogging.
+    pub async fn jog_openflexure(&self, x: i64, y: i64) -> anyhow::Result<reqwest::Response> {
+        info!("jog stage by {}, {}", x, y);
+        self.move_by(x, y, 0).await
+    }
+
+    /// Moves the stage back to the origin.
+    pub async fn home_openflexure(&self) -> anyhow::Result<reqwest::Response> {
+        info!("move stage home");
+        self.move_to(0, 0, 0).await
+    }
+
+    /// Moves the stage to an absolute position and waits until the move
+    /// finished.
+    pub async fn move_openflexure_to(&self, x: i64, y: i64, z: i64) -> anyhow::Result<()> {
+        info!("move stage to {}, {}, {}", x, y, z);
+        let response = self.move_to(x, y, z).await?.error_for_status()?;
+        let action = response
+            .json()
+            .await
+            .context("Failed to parse move response to json")?;
+        self.wait_for_action(action, ACTION_TIMEOUT).await
+    }
+
+    /// Cancels the running OpenFlexure actions, e.g. a stage move, and
+    /// returns how many were cancelled.
+    pub async fn cancel_actions(&self) -> anyhow::Result<usize> {
+        info!("cancel running actions");
+        let url = self.openflexure_url.join("api/v2/actions")?;
+        let actions: Vec<serde_json::Value> = reqwest::Client::new()
+            .get(url)
+            .send()
+            .await
+            .context("Failed to request actions")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse actions to json")?;
+
+        let mut cancelled = 0;
+        for action in &actions {
+            let status = action.get("status").and_then(|status| status.as_str());
+            let href = action.get("href").and_then(|href| href.as_str());
+            let (Some("pending" | "running"), Some(href)) = (status, href) else {
+                continue;
+            };
+            let response = reqwest::Client::new()
+                .delete(self.openflexure_url.join(href)?)
+                .send()
+                .await
+                .context("Failed to cancel action")?;
+            log_response("action", &response);
+            cancelled += 1;
+        }
+        Ok(cancelled)
+    }
+
+    /// Runs the camera stage mapping calibration of OpenFlexure, which moves
+    /// the stage by known distances and tracks how far the image shifts.
+    /// Returns the steps the stage moves per pixel of image shift.
+    pub async fn calibrate_camera_stage_mapping(&self) -> anyhow::Result<StepsPerPixel> {
+        info!("calibrate camera stage mapping");
+        let url = self
+            .openflexure_url
+            .join(&format!("{}/calibrate_xy", CAMERA_STAGE_MAPPING))?;
+        let action = reqwest::Client::new()
+            .post(url)
+            .header("content-type", "application/json")
+            .body("{}")
+            .send()
+            .await
+            .context("Failed to post calibration request")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse calibration response to json")?;
+        self.wait_for_action(action, CALIBRATION_TIMEOUT).await?;
+
+        let url = self
+            .openflexure_url
+            .join(&format!("{}/get_calibration", CAMERA_STAGE_MAPPING))?;
+        let calibration: serde_json::Value = reqwest::Client::new()
+            .get(url)
+            .send()
+            .await
+            .context("Failed to request camera stage mapping")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse camera stage mapping to json")?;
+        let matrix = find_key(&calibration, "image_to_stage_displacement")
+            .context("Camera stage mapping has no image to stage displacement")?;
+        let matrix = serde_json::from_value(matrix.clone())
+            .context("Failed to parse image to stage displacement")?;
+        debug!("image to stage displacement {:?}", matrix);
+        StepsPerPixel::from_image_to_stage(matrix).context("Camera stage mapping is degenerate")
+    }
+
+    /// Polls an OpenFlexure action until it finished. Servers which do not
+    /// link the action, like the mock server, are not waited for.
+    async fn wait_for_action(
+        &self,
+        mut action: serde_json::Value,
+        timeout: Duration,
+    ) -> anyhow::Result<()> {
+        let Some(href) = action.get("href").and_then(|href| href.as_str()) else {
+            return Ok(());
+        };
+        let url = self.openflexure_url.join(href)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            match action.get("status").and_then(|status| status.as_str()) {
+                Some("completed") | None => return Ok(()),
+                Some(status @ ("error" | "cancelled")) => bail!("Action {}", status),
+                Some(_) => {}
+            }
+            if Instant::now() >= deadline {
+                bail!("Timed out waiting for the action");
+            }
+
+            tokio::time::sleep(ACTION_POLL_INTERVAL).await;
+            action = reqwest::Client::new()
+                .get(url.clone())
+                .send()
+                .await
+                .context("Failed to poll action")?
+                .json()
+                .await
+                .context("Failed to parse action to json")?;
+        }
+    }
+
+    async fn move_by(&self, x: i64, y: i64, z: i64) -> anyhow::Result<reqwest::Response> {
         let current_pos = self.get_openflexure_position().await?; // TODO: not optimal
+        self.move_to(current_pos.x + x, current_pos.y + y, current_pos.z + z)
+            .await
+    }
 
+    /// Moves the stage to an absolute position. Every stage move goes
+    /// through here, so the target is checked against the soft limits.
+    async fn move_to(&self, x: i64, y: i64, z: i64) -> anyhow::Result<reqwest::Response> {
+        let OpenFlexurePosition { x, y, z } = self.limits.check(OpenFlexurePosition { x, y, z })?;
         let url = self.openflexure_url.join("api/v2/actions/stage/move")?;
-        let mut body = HashMap::from(match axis {
-            OpenflexureAxis::X => [
-                ("x", (current_pos.x + value).to_string()),
-                ("y", current_pos.y.to_string()),
-                ("z", current_pos.z.to_string()),
-            ],
-            OpenflexureAxis::Y => [
-                ("x", current_pos.x.to_string()),
-                ("y", (current_pos.y + value).to_string()),
-                ("z", current_pos.z.to_string()),
-            ],
-            OpenflexureAxis::Z => [
-                ("x", current_pos.x.to_string()),
-                ("y", current_pos.y.to_string()),
-                ("z", (current_pos.z + value).to_string()),
-            ],
-        });
+        let mut body = HashMap::from([
+            ("x", x.to_string()),
+            ("y", y.to_string()),
+            ("z", z.to_string()),
+        ]);
 
         body.insert("absolute", "true".to_string());
 
-        reqwest::Client::new()
+        let response = reqwest::Client::new()
             .post(url)
             .json(&body)
             .send()
             .await
-            .context("Failed to post move axis request")
+            .context("Failed to post move axis request")?;
+
+        log_response("stage", &response);
+        Ok(response)
+    }
+
+    pub async fn capture_image(&self) -> anyhow::Result<reqwest::Response> {
+        let url = self.openflexure_url.join("api/v2/actions/camera/capture")?;
+        info!("capture image");
+        let response = reqwest::Client::new()
+            .post(url)
+            .header("content-type", "application/json")
+            .body("{}")
+            .send()
+            .await
+            .context("Failed to post capture request")?;
+
+        log_response("camera", &response);
+        Ok(response)
+    }
+
+    /// Measures the round trip time to the OpenFlexure server.
+    pub async fn ping_openflexure(&self) -> anyhow::Result<Duration> {
+        ping(&self.openflexure_url).await
+    }
+
+    /// Measures the round trip time to the Phoenix server.
+    pub async fn ping_phoenix(&self) -> anyhow::Result<Duration> {
+        ping(&self.phoenix_url).await
+    }
+}
+
+/// Any answer counts, the servers do not have to serve their root.
+async fn ping(url: &url::Url) -> anyhow::Result<Duration> {
+    let start = Instant::now();
+    reqwest::Client::new()
+        .head(url.clone())
+        .timeout(PING_TIMEOUT)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", url))?;
+    Ok(start.elapsed())
+}
+
+/// Searches `value` and the objects nested in it for `key`.
+fn find_key<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    let object = value.as_object()?;
+    object
+        .get(key)
+        .or_else(|| object.values().find_map(|value| find_key(value, key)))
+}
+
+fn log_response(target: &str, response: &reqwest::Response) {
+    let status = response.status();
+    if status.is_success() {
+        info!("{} responded {}", target, status);
+    } else {
+        error!("{} responded {}", target, status);
     }
 }
@@ -0,0 +1,333 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{OpenFlexurePosition, OpenflexureAxis},
+    display::{PanelModel, ili9341::Orientation},
+    i18n::Language,
+    input::InputConfig,
+    limits::SoftLimits,
+    power::PowerConfig,
+    units::UnitConfig,
+};
+
+/// Runtime configuration of the scope UI.
+///
+/// The configuration is stored as JSON. Missing fields fall back to their
+/// defaults, so an empty object is a valid configuration file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub openflexure_url: url::Url,
+    pub phoenix_url: url::Url,
+    pub panel: PanelModel,
+    /// Orientation of the panel, depending on how it is mounted in the housing
+    pub orientation: Orientation,
+    pub input: InputConfig,
+    pub power: PowerConfig,
+    /// Servers which can be selected on the settings screen
+    pub servers: Vec<ServerPreset>,
+    pub steps: StepConfig,
+    pub language: Language,
+    /// Serial device of the Sangaboard, shown as present on the info screen
+    /// if it exists
+    pub sangaboard_device: PathBuf,
+    pub bookmarks: Vec<Bookmark>,
+    /// Software travel limits of the stage
+    pub limits: SoftLimits,
+    pub units: UnitConfig,
+}
+
+/// Stage position saved under a name on the bookmarks screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Bookmark {
+    pub fn position(&self) -> OpenFlexurePosition {
+        OpenFlexurePosition {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+}
+
+/// OpenFlexure and Phoenix server of one microscope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerPreset {
+    /// Name shown on the settings screen
+    pub name: String,
+    pub openflexure_url: url::Url,
+    pub phoenix_url: url::Url,
+}
+
+impl ServerPreset {
+    fn new(name: &str, openflexure_url: &str, phoenix_url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            openflexure_url: openflexure_url.try_into().unwrap(),
+            phoenix_url: phoenix_url.try_into().unwrap(),
+        }
+    }
+}
+
+/// Distances the stage and the slider move per step of the encoder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StepConfig {
+    /// Steps moved per step of the encoder
+    pub step_size: i64,
+    /// Steps moved per step while the button is held down
+    pub fine_step_size: i64,
+    /// Distance in µm an axis moves per step of the encoder, used instead
+    /// of the steps while the UI shows µm or mm and the axis is calibrated
+    pub step_um: i64,
+    /// Distance in µm moved per step while the button is held down
+    pub fine_step_um: i64,
+}
+
+impl Default for StepConfig {
+    fn default() -> Self {
+        Self {
+            step_size: 200,
+            fine_step_size: 20,
+            step_um: 10,
+            fine_step_um: 1,
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            openflexure_url: "http://localhost:5000".try_into().unwrap(),
+            phoenix_url: "http://localhost:4000".try_into().unwrap(),
+            panel: PanelModel::default(),
+            orientation: Orientation::LandscapeFlipped,
+            input: InputConfig::default(),
+            power: PowerConfig::default(),
+            servers: vec![
+                ServerPreset::new("Local", "http://localhost:5000", "http://localhost:4000"),
+                ServerPreset::new(
+                    "Microscope",
+                    "http://microscope.local:5000",
+                    "http://microscope.local:4000",
+                ),
+            ],
+            steps: StepConfig::default(),
+            language: Language::default(),
+            sangaboard_device: PathBuf::from("/dev/ttyACM0"),
+            bookmarks: Vec::new(),
+            limits: SoftLimits::default(),
+            units: UnitConfig::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Reads the configuration from the JSON file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Reads the configuration from `path`, falling back to the defaults if the
+    /// file does not exist or can not be parsed.
+    ///
+    /// A file which can not be parsed is moved to `<path>.bad`, so saving the
+    /// defaults after a setting was changed does not overwrite the settings
+    /// of the user. They can be fixed there and moved back.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            debug!("no config file at {}, using defaults", path.display());
+            return Self::default();
+        }
+
+        Self::load(path).unwrap_or_else(|e| {
+            warn!("{:?}, using defaults", e);
+            let bad_path = path.with_extension("json.bad");
+            // saving replaces the file in the same directory, so it fails as
+            // well if the file can not be moved
+            match fs::rename(path, &bad_path) {
+                Ok(()) => warn!("moved config file to {}", bad_path.display()),
+                Err(e) => error!("failed to move config file aside: {:?}", e),
+            }
+            Self::default()
+        })
+    }
+
+    /// Writes the configuration as pretty printed JSON to `path`.
+    ///
+    /// The file is replaced at once, so a power loss while saving keeps the
+    /// previous settings.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(self)?;
+
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)
+            .with_context(|| format!("Failed to write config file {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace config file {}", path.display()))
+    }
+
+    /// Steps `axis` moves per step of the encoder, from the step size in µm
+    /// if the axis is shown in µm or mm.
+    pub fn step_distance(&self, axis: OpenflexureAxis, fine: bool) -> i64 {
+        if self.units.is_physical(axis) {
+            let um = if fine {
+                self.steps.fine_step_um
+            } else {
+                self.steps.step_um
+            };
+            // a step of the encoder always moves
+            self.units.um_to_steps(axis, um).max(1)
+        } else if fine {
+            self.steps.fine_step_size
+        } else {
+            self.steps.step_size
+        }
+    }
+
+    /// Index of the server preset matching the configured URLs.
+    pub fn server_idx(&self) -> Option<usize> {
+        self.servers.iter().position(|server| {
+            server.openflexure_url == self.openflexure_url && server.phoenix_url == self.phoenix_url
+        })
+    }
+
+    /// Switches to the server preset following the current one.
+    pub fn select_next_server(&mut self) {
+        if self.servers.is_empty() {
+            return;
+        }
+        let idx = self
+            .server_idx()
+            .map_or(0, |idx| (idx + 1) % self.servers.len());
+        let server = &self.servers[idx];
+        self.openflexure_url = server.openflexure_url.clone();
+        self.phoenix_url = server.phoenix_url.clone();
+    }
+
+    /// First free bookmark name of the form `P1`, `P2`, ...
+    pub fn next_bookmark_name(&self) -> String {
+        (1..)
+            .map(|idx| format!("P{}", idx))
+            .find(|name| !self.bookmarks.iter().any(|bookmark| bookmark.name == *name))
+            .unwrap()
+    }
+
+    /// Saves `position` under `name`, replacing a bookmark with the same name.
+    pub fn set_bookmark(&mut self, name: String, position: OpenFlexurePosition) {
+        let bookmark = Bookmark {
+            name,
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        };
+        match self.bookmarks.iter_mut().find(|b| b.name == bookmark.name) {
+            Some(existing) => *existing = bookmark,
+            None => self.bookmarks.push(bookmark),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Unit;
+
+    #[test]
+    fn test_select_next_server() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.server_idx(), Some(0));
+        config.select_next_server();
+        assert_eq!(config.server_idx(), Some(1));
+        assert_eq!(config.openflexure_url.host_str(), Some("microscope.local"));
+        config.select_next_server();
+        assert_eq!(config.server_idx(), Some(0));
+
+        // URLs set in the config file select the first preset
+        config.openflexure_url = "http://10.0.0.2:5000".try_into().unwrap();
+        assert_eq!(config.server_idx(), None);
+        config.select_next_server();
+        assert_eq!(config.server_idx(), Some(0));
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut config = AppConfig::default();
+        let position = |x| OpenFlexurePosition { x, y: 0, z: 0 };
+        assert_eq!(config.next_bookmark_name(), "P1");
+        config.set_bookmark(config.next_bookmark_name(), position(1));
+        config.set_bookmark("SAMPLE".to_string(), position(2));
+        assert_eq!(config.next_bookmark_name(), "P2");
+
+        config.bookmarks.remove(0);
+        assert_eq!(config.next_bookmark_name(), "P1");
+        config.set_bookmark("SAMPLE".to_string(), position(3));
+        assert_eq!(config.bookmarks.len(), 1);
+        assert_eq!(config.bookmarks[0].position(), position(3));
+    }
+
+    #[test]
+    fn test_step_distance() {
+        let mut config = AppConfig::default();
+        config.units.unit = Unit::Micrometre;
+        config.units.steps_per_um.x = Some(0.01);
+        // only X is calibrated
+        assert_eq!(config.step_distance(OpenflexureAxis::X, true), 1);
+        assert_eq!(config.step_distance(OpenflexureAxis::X, false), 1);
+        assert_eq!(config.step_distance(OpenflexureAxis::Y, false), 200);
+
+        config.units.steps_per_um.x = Some(2.0);
+        assert_eq!(config.step_distance(OpenflexureAxis::X, false), 20);
+    }
+
+    #[test]
+    fn test_settings_survive_save() {
+        let path = std::env::temp_dir().join(format!("scope-ui-test-{}.json", std::process::id()));
+        let mut config = AppConfig::default();
+        config.steps.step_size = 50;
+        config.language = Language::German;
+        config.save(&path).unwrap();
+
+        let loaded = AppConfig::load_or_default(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.steps.step_size, 50);
+        assert_eq!(loaded.language, Language::German);
+    }
+
+    #[test]
+    fn test_broken_file_survives_save() {
+        let dir = std::env::temp_dir().join(format!("scope-ui-test-bad-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let content = r#"{"steps": {"step_size": 50}, "language": "klingon"}"#;
+        fs::write(&path, content).unwrap();
+
+        let config = AppConfig::load_or_default(&path);
+        assert_eq!(config.steps.step_size, StepConfig::default().step_size);
+        config.save(&path).unwrap();
+
+        let bad = fs::read_to_string(dir.join("config.json.bad")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(bad, content);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+use crate::{
+    display::{Panel, Scroller},
+    logging::LogBuffer,
+    theme::Theme,
+};
+
+/// Space above and below the header title
+const HEADER_PADDING: u32 = 2;
+
+/// Console streaming the records of a [`LogBuffer`] below a fixed header.
+///
+/// If the panel supports hardware scrolling, new lines scroll the console
+/// area up and only the new line is drawn. Otherwise the console area is
+/// redrawn with the latest lines.
+pub struct LogConsole {
+    buffer: LogBuffer,
+    seen: u64,
+    scroller: Option<Scroller>,
+    /// Lines shown in the console area, only kept without hardware scrolling
+    lines: VecDeque<String>,
+    /// Number of rows drawn since entering the console
+    filled: u32,
+}
+
+/// Position of the console area for the current display size and font.
+struct Layout {
+    header: Rectangle,
+    top: i32,
+    width: u32,
+    line_height: u32,
+    rows: u32,
+    columns: usize,
+    fixed_bottom: u32,
+}
+
+impl Layout {
+    fn new(bounding_box: Rectangle, theme: &Theme) -> Self {
+        let char_size = theme.font.character_size;
+        let header_height = char_size.height + 2 * HEADER_PADDING + 1;
+        let console_height = bounding_box.size.height.saturating_sub(header_height);
+        let rows = console_height / char_size.height;
+
+        Self {
+            header: Rectangle::new(
+                bounding_box.top_left,
+                Size::new(bounding_box.size.width, header_height),
+            ),
+            top: bounding_box.top_left.y + header_height as i32,
+            width: bounding_box.size.width,
+            line_height: char_size.height,
+            rows,
+            columns: (bounding_box.size.width / char_size.width) as usize,
+            fixed_bottom: console_height - rows * char_size.height,
+        }
+    }
+
+    fn row(&self, y: i32) -> Rectangle {
+        Rectangle::new(Point::new(0, y), Size::new(self.width, self.line_height))
+    }
+}
+
+impl LogConsole {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self {
+            buffer,
+            seen: 0,
+            scroller: None,
+            lines: VecDeque::new(),
+            filled: 0,
+        }
+    }
+
+    /// Whether new lines are scrolled in by the panel.
+    pub fn scrolls_in_hardware(&self) -> bool {
+        self.scroller.is_some()
+    }
+
+    /// Clears the display and draws the header and the latest lines.
+    pub fn enter<D>(&mut self, display: &mut D, theme: &Theme) -> Result<(), D::Error>
+    where
+        D: Panel,
+    {
+        let layout = Layout::new(display.bounding_box(), theme);
+        display.clear(theme.background)?;
+        self.draw_header(display, theme, &layout)?;
+
+        self.scroller = display
+            .vertical_scroll(layout.header.size.height as u16, layout.fixed_bottom as u16)
+            .unwrap_or_else(|e| {
+                log::warn!("failed to set up hardware scrolling: {:?}", e);
+                None
+            });
+        if self.scroller.is_none() {
+            // e.g. the ILI9341 only scrolls along its long side
+            log::info!("no hardware scrolling in this orientation, redrawing the log");
+        }
+        self.filled = 0;
+        self.lines.clear();
+
+        let (lines, total) = self.buffer.since(0);
+        self.seen = total;
+        let skip = lines.len().saturating_sub(layout.rows as usize);
+        self.append(display, theme, &layout, lines.into_iter().skip(skip))?;
+
+        let _ = display.flush();
+        Ok(())
+    }
+
+    /// Appends the lines logged since the last update.
+    pub fn update<D>(&mut self, display: &mut D, theme: &Theme) -> Result<(), D::Error>
+    where
+        D: Panel,
+    {
+        let (lines, total) = self.buffer.since(self.seen);
+        if lines.is_empty() {
+            return Ok(());
+        }
+        self.seen = total;
+
+        let layout = Layout::new(display.bounding_box(), theme);
+        self.append(display, theme, &layout, lines.into_iter())?;
+
+        let _ = display.flush();
+        Ok(())
+    }
+
+    /// Restores the regular display mapping if hardware scrolling was used.
+    pub fn exit<D>(&mut self, display: &mut D)
+    where
+        D: Panel,
+    {
+        if self.scroller.take().is_some()
+            && let Err(e) = display.reset_scroll()
+        {
+            log::warn!("failed to reset hardware scrolling: {:?}", e);
+        }
+    }
+
+    fn draw_header<D>(
+        &self,
+        display: &mut D,
+        theme: &Theme,
+        layout: &Layout,
+    ) -> Result<(), D::Error>
+    where
+        D: Panel,
+    {
+        Text::with_baseline(
+            "Log",
+            layout.header.top_left + Point::new(0, HEADER_PADDING as i32),
+            MonoTextStyle::new(theme.font, theme.accent),
+            Baseline::Top,
+        )
+        .draw(display)?;
+
+        let y = layout.top - 1;
+        Line::new(Point::new(0, y), Point::new(layout.width as i32 - 1, y))
+            .into_styled(PrimitiveStyle::with_stroke(theme.muted, 1))
+            .draw(display)
+    }
+
+    fn append<D>(
+        &mut self,
+        display: &mut D,
+        theme: &Theme,
+        layout: &Layout,
+        lines: impl Iterator<Item = String>,
+    ) -> Result<(), D::Error>
+    where
+        D: Panel,
+    {
+        if layout.rows == 0 {
+            return Ok(());
+        }
+
+        let Some(scroller) = self.scroller.as_mut() else {
+            for line in lines {
+                if self.lines.len() == layout.rows as usize {
+                    self.lines.pop_front();
+                }
+                self.lines.push_back(line);
+            }
+
+            display.fill_solid(
+                &Rectangle::new(
+                    Point::new(0, layout.top),
+                    Size::new(layout.width, layout.rows * layout.line_height),
+                ),
+                theme.background,
+            )?;
+            for (row, line) in self.lines.iter().enumerate() {
+                let y = layout.top + (row as u32 * layout.line_height) as i32;
+                draw_line(display, theme, layout, y, line)?;
+            }
+            return Ok(());
+        };
+
+        for line in lines {
+            let y = if self.filled < layout.rows {
+                let y = layout.top + (self.filled * layout.line_height) as i32;
+                self.filled += 1;
+                y
+            } else {
+                // the oldest line moves from the top to the bottom of the
+                // scrolling area, so its memory lines are reused
+                let y = scroller.top_offset() as i32;
+                display
+                    .scroll(scroller, layout.line_height as u16)
+                    .unwrap_or_else(|e| log::warn!("failed to scroll display: {:?}", e));
+                display.fill_solid(&layout.row(y), theme.background)?;
+                y
+            };
+            draw_line(display, theme, layout, y, &line)?;
+        }
+        Ok(())
+    }
+}
+
+fn draw_line<D>(
+    display: &mut D,
+    theme: &Theme,
+    layout: &Layout,
+    y: i32,
+    line: &str,
+) -> Result<(), D::Error>
+where
+    D: Panel,
+{
+    let color = match line.chars().next() {
+        Some('E') => theme.accent,
+        _ => theme.text,
+    };
+    let line: String = line.chars().take(layout.columns).collect();
+
+    Text::with_baseline(
+        &line,
+        Point::new(0, y),
+        MonoTextStyle::new(theme.font, color),
+        Baseline::Top,
+    )
+    .draw(display)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{ili9341::Orientation, simulated::SimulatedPanel};
+
+    fn panel(orientation: Orientation) -> SimulatedPanel {
+        SimulatedPanel::new(Size::new(320, 240), orientation)
+    }
+
+    fn push_lines(buffer: &LogBuffer, lines: std::ops::Range<u32>) {
+        for line in lines {
+            let level = if line % 5 == 0 { 'E' } else { 'I' };
+            buffer.push(format!("{} line {}", level, line));
+        }
+    }
+
+    /// Draws the latest lines of `buffer` into a new console, as reference
+    fn entered(buffer: &LogBuffer, orientation: Orientation) -> Vec<u8> {
+        let mut display = panel(orientation);
+        LogConsole::new(buffer.clone())
+            .enter(&mut display, &Theme::color())
+            .unwrap();
+        display.to_ppm()
+    }
+
+    #[test]
+    fn test_append_without_scrolling() {
+        let theme = Theme::color();
+        let buffer = LogBuffer::new(100);
+        push_lines(&buffer, 0..3);
+        let mut display = panel(Orientation::Landscape);
+        let mut console = LogConsole::new(buffer.clone());
+        console.enter(&mut display, &theme).unwrap();
+        assert!(console.scroller.is_none());
+
+        push_lines(&buffer, 3..5);
+        console.update(&mut display, &theme).unwrap();
+        assert_eq!(display.to_ppm(), entered(&buffer, Orientation::Landscape));
+
+        // more lines than fit, the oldest ones are dropped
+        push_lines(&buffer, 5..40);
+        console.update(&mut display, &theme).unwrap();
+        assert_eq!(display.to_ppm(), entered(&buffer, Orientation::Landscape));
+    }
+
+    #[test]
+    fn test_scrolling_wraps_around() {
+        let theme = Theme::color();
+        let buffer = LogBuffer::new(100);
+        push_lines(&buffer, 0..3);
+        let mut display = panel(Orientation::Portrait);
+        let mut console = LogConsole::new(buffer.clone());
+        console.enter(&mut display, &theme).unwrap();
+        assert!(console.scroller.is_some());
+
+        // line by line until the scrolling area wrapped around twice
+        for line in 3..48 {
+            push_lines(&buffer, line..line + 1);
+            console.update(&mut display, &theme).unwrap();
+            assert_eq!(display.to_ppm(), entered(&buffer, Orientation::Portrait));
+        }
+
+        console.exit(&mut display);
+        assert!(console.scroller.is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! System information shown on the info screen.
+//!
+//! Everything is read from procfs and sysfs below a root directory, so the
+//! parsing can be tested against a fake root.
+
+use std::{
+    fs,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use nix::sys::statvfs::statvfs;
+
+/// Version of the crate and the commit it was built from
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_HASH: &str = env!("GIT_HASH");
+
+/// Reads information about the system the UI runs on.
+#[derive(Debug, Clone)]
+pub struct SystemInfo {
+    root: PathBuf,
+}
+
+impl SystemInfo {
+    /// Reads from the filesystem at `root`, `/` on the device.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(path)).ok()
+    }
+
+    pub fn hostname(&self) -> Option<String> {
+        let hostname = self.read("proc/sys/kernel/hostname")?;
+        Some(hostname.trim().to_string())
+    }
+
+    /// IPv4 addresses of the network interfaces, without the loopback
+    /// addresses.
+    pub fn ip_addresses(&self) -> Vec<Ipv4Addr> {
+        self.read("proc/net/fib_trie")
+            .map(|fib_trie| parse_local_addresses(&fib_trie))
+            .unwrap_or_default()
+    }
+
+    /// Time since the system booted.
+    pub fn uptime(&self) -> Option<Duration> {
+        let uptime = self.read("proc/uptime")?;
+        let secs = uptime.split_whitespace().next()?.parse::<f64>().ok()?;
+        Some(Duration::from_secs_f64(secs))
+    }
+
+    /// Temperature of the CPU in °C.
+    pub fn cpu_temperature(&self) -> Option<f32> {
+        let millidegrees = self.read("sys/class/thermal/thermal_zone0/temp")?;
+        let millidegrees = millidegrees.trim().parse::<i32>().ok()?;
+        Some(millidegrees as f32 / 1000.0)
+    }
+
+    /// Bytes available to the user on the filesystem holding the root, the
+    /// SD card on the device.
+    pub fn disk_free(&self) -> Option<u64> {
+        let stat = statvfs(&self.root).ok()?;
+        Some(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+    }
+
+    /// Whether the serial device of the Sangaboard exists. `device` is an
+    /// absolute path like `/dev/ttyACM0`.
+    pub fn device_exists(&self, device: &Path) -> bool {
+        let device = device.strip_prefix("/").unwrap_or(device);
+        self.root.join(device).exists()
+    }
+}
+
+/// Collects the addresses the kernel marks as local in `/proc/net/fib_trie`,
+/// which lists every address as `|-- 192.168.1.5` followed by
+/// `/32 host LOCAL`.
+fn parse_local_addresses(fib_trie: &str) -> Vec<Ipv4Addr> {
+    let mut addresses = Vec::new();
+    let mut last = None;
+    for line in fib_trie.lines() {
+        let line = line.trim();
+        if let Some(address) = line.strip_prefix("|-- ") {
+            last = address.parse::<Ipv4Addr>().ok();
+        } else if line.starts_with("/32 host LOCAL")
+            && let Some(address) = last.take()
+            && !address.is_loopback()
+            && !addresses.contains(&address)
+        {
+            addresses.push(address);
+        }
+    }
+    addresses
+}
+
+/// Formats a duration like an uptime, e.g. `2d 3h` or `4h 12m`.
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Formats a number of bytes with a binary unit, e.g. `12.3 GB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if unit == "B" {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIB_TRIE: &str = "Main:
+  +-- 0.0.0.0/0 3 0 5
+     |-- 0.0.0.0
+        /0 universe UNICAST
+     +-- 127.0.0.0/8 2 0 2
+        +-- 127.0.0.0/31 1 0 0
+           |-- 127.0.0.0
+              /8 host LOCAL
+           |-- 127.0.0.1
+              /32 host LOCAL
+        |-- 127.255.255.255
+           /32 link BROADCAST
+     +-- 192.168.1.0/24 2 0 2
+        +-- 192.168.1.0/28 2 0 2
+           |-- 192.168.1.0
+              /24 link UNICAST
+           |-- 192.168.1.5
+              /32 host LOCAL
+        |-- 192.168.1.255
+           /32 link BROADCAST
+Local:
+  +-- 0.0.0.0/0 3 0 5
+     +-- 192.168.1.0/24 2 0 2
+           |-- 192.168.1.5
+              /32 host LOCAL
+";
+
+    /// Fake root with the files read by [`SystemInfo`]
+    fn fake_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "scope-ui-diagnostics-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let files = [
+            ("proc/sys/kernel/hostname", "microscope\n"),
+            ("proc/net/fib_trie", FIB_TRIE),
+            ("proc/uptime", "93784.52 361234.10\n"),
+            ("sys/class/thermal/thermal_zone0/temp", "48312\n"),
+            ("dev/ttyACM0", ""),
+        ];
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_reads_fake_root() {
+        let root = fake_root();
+        let system = SystemInfo::new(&root);
+
+        assert_eq!(system.hostname().as_deref(), Some("microscope"));
+        assert_eq!(system.ip_addresses(), vec![Ipv4Addr::new(192, 168, 1, 5)]);
+        assert_eq!(system.uptime().map(|u| u.as_secs()), Some(93784));
+        assert_eq!(system.cpu_temperature(), Some(48.312));
+        assert!(system.disk_free().is_some());
+        assert!(system.device_exists(Path::new("/dev/ttyACM0")));
+        assert!(!system.device_exists(Path::new("/dev/ttyAMA0")));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let system = SystemInfo::new("/nonexistent");
+        assert_eq!(system.hostname(), None);
+        assert!(system.ip_addresses().is_empty());
+        assert_eq!(system.uptime(), None);
+        assert_eq!(system.disk_free(), None);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_duration(Duration::from_secs(93784)), "1d 2h");
+        assert_eq!(
+            format_duration(Duration::from_secs(4 * 3600 + 720)),
+            "4h 12m"
+        );
+        assert_eq!(format_duration(Duration::from_secs(59)), "0m");
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(13_207_024_435), "12.3 GB");
+    }
+}
@@ -3,8 +3,9 @@ pub use display_interface::DisplayError;
 use display_interface::WriteOnlyDataCommand;
 use embedded_hal::delay::DelayNs;
 use embedded_hal::digital::OutputPin;
+use serde::{Deserialize, Serialize};
 
-use super::Flushable;
+use super::{Flushable, PanelOrientation, PanelPower, Scroller, VerticalScroll};
 
 type Result<T = (), E = DisplayError> = core::result::Result<T, E>;
 
@@ -45,6 +46,8 @@ pub trait Mode {
 
 /// The default implementation of the Mode trait from above
 /// Should work for most (but not all) boards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Orientation {
     Portrait,
     PortraitFlipped,
@@ -92,11 +95,12 @@ impl Pixel {
 const DISPLAY_WIDTH: usize = 320;
 const DISPLAY_HEIGHT: usize = 240;
 
-struct PixelBuff([Pixel; DISPLAY_WIDTH as usize * DISPLAY_HEIGHT as usize]);
+/// Frame sized buffer, allocated on the heap as it is too large for the stack
+struct PixelBuff(Box<[Pixel]>);
 
 impl Default for PixelBuff {
     fn default() -> Self {
-        let mut buff = Vec::with_capacity((DISPLAY_WIDTH * DISPLAY_HEIGHT).into());
+        let mut buff = Vec::with_capacity(DISPLAY_WIDTH * DISPLAY_HEIGHT);
         for y in 0..DISPLAY_HEIGHT {
             for x in 0..DISPLAY_WIDTH {
                 buff.push(Pixel {
@@ -106,14 +110,7 @@ impl Default for PixelBuff {
                 });
             }
         }
-        let buff = buff.try_into().unwrap_or_else(|v: Vec<Pixel>| {
-            panic!(
-                "Expected a Vec of length {} but it was {}",
-                DISPLAY_WIDTH * DISPLAY_HEIGHT,
-                v.len()
-            )
-        });
-        Self(buff)
+        Self(buff.into_boxed_slice())
     }
 }
 
@@ -171,7 +168,7 @@ where
 
         // Do hardware reset by holding reset low for at least 10us
         ili9341.reset.set_low().map_err(|_| DisplayError::RSError)?;
-        let _ = delay.delay_ms(1);
+        delay.delay_ms(1);
         // Set high for normal operation
         ili9341
             .reset
@@ -180,14 +177,14 @@ where
 
         // Wait 5ms after reset before sending commands
         // and 120ms before sending Sleep Out
-        let _ = delay.delay_ms(5);
+        delay.delay_ms(5);
 
         // Do software reset
         ili9341.command(Command::SoftwareReset, &[])?;
 
         // Wait 5ms after reset before sending commands
         // and 120ms before sending Sleep Out
-        let _ = delay.delay_ms(120);
+        delay.delay_ms(120);
 
         ili9341.set_orientation(mode)?;
 
@@ -197,7 +194,7 @@ where
         ili9341.sleep_mode(ModeState::Off)?;
 
         // Wait 5ms after Sleep Out before sending commands
-        let _ = delay.delay_ms(5);
+        delay.delay_ms(5);
 
         ili9341.display_mode(ModeState::On)?;
 
@@ -257,7 +254,7 @@ where
         } else {
             self.height
         } as u16;
-        let scroll_lines = height as u16 - fixed_top_lines - fixed_bottom_lines;
+        let scroll_lines = height - fixed_top_lines - fixed_bottom_lines;
 
         self.command(
             Command::VerticalScrollDefine,
@@ -276,7 +273,7 @@ where
 
     pub fn scroll_vertically(&mut self, scroller: &mut Scroller, num_lines: u16) -> Result {
         scroller.top_offset += num_lines;
-        if scroller.top_offset > (scroller.height - scroller.fixed_bottom_lines) {
+        if scroller.top_offset >= (scroller.height - scroller.fixed_bottom_lines) {
             scroller.top_offset = scroller.fixed_top_lines
                 + (scroller.top_offset + scroller.fixed_bottom_lines - scroller.height)
         }
@@ -290,6 +287,12 @@ where
         )
     }
 
+    /// Restores the regular display mapping after scrolling.
+    pub fn reset_vertical_scroll(&mut self) -> Result {
+        let mut scroller = self.configure_vertical_scroll(0, 0)?;
+        self.scroll_vertically(&mut scroller, 0)
+    }
+
     /// Draw a rectangle on the screen, represented by top-left corner (x0, y0)
     /// and bottom-right corner (x1, y1).
     ///
@@ -353,7 +356,7 @@ where
 
     /// Fill entire screen with specfied color u16 value
     pub fn clear_screen(&mut self, color: u16) -> Result {
-        let color = core::iter::repeat(color).take(self.width * self.height);
+        let color = core::iter::repeat_n(color, self.width * self.height);
         self.draw_raw_iter(0, 0, self.width as u16, self.height as u16, color)
     }
 
@@ -440,17 +443,22 @@ where
             (default_min, default_max),
             |acc, (prev, new)| {
                 if prev.color ^ new.color == 0 {
-                    return acc;
+                    acc
                 } else {
                     let (min, max) = acc;
-                    return (
+                    (
                         Pixel::new(new.x.min(min.x), new.y.min(min.y), new.color),
                         Pixel::new(new.x.max(max.x), new.y.max(max.y), new.color),
-                    );
+                    )
                 }
             },
         );
 
+        if min.x > max.x || min.y > max.y {
+            // nothing changed since the last flush
+            return Ok(());
+        }
+
         let mut data = Vec::with_capacity(
             ((max.x.wrapping_sub(min.x).wrapping_add(1))
                 * (max.y.wrapping_sub(min.y).wrapping_add(1)))
@@ -468,25 +476,77 @@ where
             }
         }
         // dbg!(min, max, &data);
-        self.drawn_buffer.0 = self.buffer.0.clone();
-        self.buffer = PixelBuff::default();
+        self.drawn_buffer = std::mem::take(&mut self.buffer);
         self.draw_raw_slice(min.x, min.y, max.x, max.y, &mut data)
             .map_err(|e| {
-                match e {
-                    DisplayError::BusWriteError => {
-                        println!("Failed to write to display {:?}", e);
-                        dbg!(min, max);
-                        println!("0x{:02X?}", data);
-                        print(min, max, &data);
-                        println!();
-                    }
-                    _ => {}
+                if let DisplayError::BusWriteError = e {
+                    println!("Failed to write to display {:?}", e);
+                    dbg!(min, max);
+                    println!("0x{:02X?}", data);
+                    print(min, max, &data);
+                    println!();
                 }
-                return e;
+                e
             })
     }
 }
 
+impl<IFACE, RESET> PanelPower for Ili9341<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    fn set_sleep(&mut self, sleep: bool) -> Result {
+        if sleep {
+            self.display_mode(ModeState::Off)?;
+            self.sleep_mode(ModeState::On)
+        } else {
+            self.sleep_mode(ModeState::Off)?;
+            // Wait 5ms after Sleep Out before sending commands
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            self.display_mode(ModeState::On)
+        }
+    }
+}
+
+impl<IFACE, RESET> PanelOrientation for Ili9341<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    fn set_orientation(&mut self, orientation: Orientation) -> Result {
+        Ili9341::set_orientation(self, orientation)
+    }
+}
+
+impl<IFACE, RESET> VerticalScroll for Ili9341<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// The hardware scrolls along the long side of the panel, which is only
+    /// vertical in portrait orientation.
+    fn can_scroll(&self, orientation: Orientation) -> bool {
+        !orientation.is_landscape()
+    }
+
+    fn vertical_scroll(&mut self, fixed_top: u16, fixed_bottom: u16) -> Result<Option<Scroller>> {
+        if self.landscape {
+            return Ok(None);
+        }
+        self.configure_vertical_scroll(fixed_top, fixed_bottom)
+            .map(Some)
+    }
+
+    fn scroll(&mut self, scroller: &mut Scroller, lines: u16) -> Result {
+        self.scroll_vertically(scroller, lines)
+    }
+
+    fn reset_scroll(&mut self) -> Result {
+        if self.landscape {
+            return Ok(());
+        }
+        self.reset_vertical_scroll()
+    }
+}
+
 impl<IFACE, RESET> Ili9341<IFACE, RESET> {
     /// Get the current screen width. It can change based on the current orientation
     pub fn width(&self) -> usize {
@@ -499,26 +559,6 @@ impl<IFACE, RESET> Ili9341<IFACE, RESET> {
     }
 }
 
-/// Scroller must be provided in order to scroll the screen. It can only be obtained
-/// by configuring the screen for scrolling.
-pub struct Scroller {
-    top_offset: u16,
-    fixed_bottom_lines: u16,
-    fixed_top_lines: u16,
-    height: u16,
-}
-
-impl Scroller {
-    fn new(fixed_top_lines: u16, fixed_bottom_lines: u16, height: u16) -> Scroller {
-        Scroller {
-            top_offset: fixed_top_lines,
-            fixed_top_lines,
-            fixed_bottom_lines,
-            height,
-        }
-    }
-}
-
 /// Available Adaptive Brightness values
 pub enum AdaptiveBrightness {
     Off = 0x00,
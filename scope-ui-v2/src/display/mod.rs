@@ -1,9 +1,148 @@
+use std::fmt::Debug;
+
 use ::ili9341::DisplayError;
+use embedded_graphics::{
+    pixelcolor::Rgb565,
+    prelude::{DrawTarget, Size},
+};
+use serde::{Deserialize, Serialize};
+
+use self::ili9341::Orientation;
 
 pub mod graphics_core;
 pub mod ili9341;
+pub mod mono;
+pub mod simulated;
+pub mod ssd1306;
+pub mod st77xx;
 
 pub trait Flushable {
     fn flush(&mut self) -> Result<(), DisplayError>;
 }
 
+/// Panels that can be put into a low power sleep mode.
+pub trait PanelPower {
+    /// Turns the panel off and enters sleep mode, or wakes it up again.
+    fn set_sleep(&mut self, sleep: bool) -> Result<(), DisplayError>;
+}
+
+/// Panels which can be rotated at runtime.
+pub trait PanelOrientation {
+    /// Rotates the panel. Width and height are swapped when switching between
+    /// landscape and portrait, the content has to be redrawn afterwards.
+    fn set_orientation(&mut self, orientation: Orientation) -> Result<(), DisplayError>;
+}
+
+/// Panels with hardware accelerated vertical scrolling.
+///
+/// Drawing still uses frame memory coordinates while scrolling, the scroll
+/// offset only changes which memory line is shown at the top of the
+/// scrolling area.
+pub trait VerticalScroll {
+    /// Whether the panel can scroll vertically in `orientation`.
+    fn can_scroll(&self, orientation: Orientation) -> bool {
+        let _ = orientation;
+        false
+    }
+
+    /// Defines a scrolling area between `fixed_top` and `fixed_bottom` lines
+    /// which are not scrolled.
+    ///
+    /// Returns `None` if the panel can not scroll vertically in its current
+    /// orientation.
+    fn vertical_scroll(
+        &mut self,
+        fixed_top: u16,
+        fixed_bottom: u16,
+    ) -> Result<Option<Scroller>, DisplayError> {
+        let _ = (fixed_top, fixed_bottom);
+        Ok(None)
+    }
+
+    /// Scrolls the content of the scrolling area up by `lines`.
+    fn scroll(&mut self, scroller: &mut Scroller, lines: u16) -> Result<(), DisplayError> {
+        let _ = (scroller, lines);
+        Ok(())
+    }
+
+    /// Restores the regular mapping of the frame memory to the display.
+    fn reset_scroll(&mut self) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+/// Scroller must be provided in order to scroll the screen. It can only be obtained
+/// by configuring the screen for scrolling.
+pub struct Scroller {
+    pub(crate) top_offset: u16,
+    pub(crate) fixed_bottom_lines: u16,
+    pub(crate) fixed_top_lines: u16,
+    pub(crate) height: u16,
+}
+
+impl Scroller {
+    pub(crate) fn new(fixed_top_lines: u16, fixed_bottom_lines: u16, height: u16) -> Scroller {
+        Scroller {
+            top_offset: fixed_top_lines,
+            fixed_top_lines,
+            fixed_bottom_lines,
+            height,
+        }
+    }
+
+    /// Frame memory line currently shown at the top of the scrolling area.
+    pub fn top_offset(&self) -> u16 {
+        self.top_offset
+    }
+}
+
+/// Everything the UI needs from a display, implemented for all panel drivers.
+///
+/// The UI owns the panel, so screens can keep actions on the [`crate::ui::Context`]
+/// around, e.g. to apply an edited setting.
+pub trait Panel:
+    'static
+    + DrawTarget<Color = Rgb565, Error: Debug>
+    + Flushable
+    + PanelPower
+    + PanelOrientation
+    + VerticalScroll
+{
+}
+
+impl<T> Panel for T where
+    T: 'static
+        + DrawTarget<Color = Rgb565, Error: Debug>
+        + Flushable
+        + PanelPower
+        + PanelOrientation
+        + VerticalScroll
+{
+}
+
+/// Display controller of the attached panel, selected in the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelModel {
+    /// 2.4"/2.8" 240x320 ILI9341 panel
+    #[default]
+    Ili9341,
+    /// 1.8" 128x160 ST7735 panel
+    St7735,
+    /// 1.3"/1.54" 240x240 ST7789 panel
+    St7789,
+    /// 0.96"/1.3" 128x64 SSD1306 OLED connected over I²C
+    Ssd1306,
+}
+
+impl PanelModel {
+    /// Number of pixels in landscape orientation.
+    pub fn resolution(self) -> Size {
+        match self {
+            Self::Ili9341 => Size::new(320, 240),
+            Self::St7735 => Size::new(160, 128),
+            Self::St7789 => Size::new(240, 240),
+            Self::Ssd1306 => Size::new(128, 64),
+        }
+    }
+}
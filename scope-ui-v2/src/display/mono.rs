@@ -0,0 +1,129 @@
+use display_interface::DisplayError;
+use embedded_graphics::{
+    pixelcolor::{BinaryColor, Rgb565},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+use super::{
+    Flushable, PanelOrientation, PanelPower, Scroller, VerticalScroll, ili9341::Orientation,
+};
+use crate::theme::Theme;
+
+/// Adapter drawing the [`Rgb565`] UI on a monochrome panel.
+///
+/// Every color is mapped to [`BinaryColor`] with [`Theme::to_binary`], so the
+/// wrapped panel can be used wherever the UI expects a color display.
+pub struct MonoPanel<D> {
+    inner: D,
+    theme: Theme,
+}
+
+impl<D> MonoPanel<D> {
+    pub fn new(inner: D, theme: Theme) -> Self {
+        Self { inner, theme }
+    }
+
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+}
+
+impl<D> Dimensions for MonoPanel<D>
+where
+    D: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.inner.bounding_box()
+    }
+}
+
+impl<D> DrawTarget for MonoPanel<D>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    type Color = Rgb565;
+
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let theme = self.theme;
+        self.inner.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, theme.to_binary(color))),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let theme = self.theme;
+        self.inner
+            .fill_contiguous(area, colors.into_iter().map(|color| theme.to_binary(color)))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.inner.fill_solid(area, self.theme.to_binary(color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.inner.clear(self.theme.to_binary(color))
+    }
+}
+
+impl<D> Flushable for MonoPanel<D>
+where
+    D: Flushable,
+{
+    fn flush(&mut self) -> Result<(), DisplayError> {
+        self.inner.flush()
+    }
+}
+
+impl<D> PanelPower for MonoPanel<D>
+where
+    D: PanelPower,
+{
+    fn set_sleep(&mut self, sleep: bool) -> Result<(), DisplayError> {
+        self.inner.set_sleep(sleep)
+    }
+}
+
+impl<D> PanelOrientation for MonoPanel<D>
+where
+    D: PanelOrientation,
+{
+    fn set_orientation(&mut self, orientation: Orientation) -> Result<(), DisplayError> {
+        self.inner.set_orientation(orientation)
+    }
+}
+
+impl<D> VerticalScroll for MonoPanel<D>
+where
+    D: VerticalScroll,
+{
+    fn can_scroll(&self, orientation: Orientation) -> bool {
+        self.inner.can_scroll(orientation)
+    }
+
+    fn vertical_scroll(
+        &mut self,
+        fixed_top: u16,
+        fixed_bottom: u16,
+    ) -> Result<Option<Scroller>, DisplayError> {
+        self.inner.vertical_scroll(fixed_top, fixed_bottom)
+    }
+
+    fn scroll(&mut self, scroller: &mut Scroller, lines: u16) -> Result<(), DisplayError> {
+        self.inner.scroll(scroller, lines)
+    }
+
+    fn reset_scroll(&mut self) -> Result<(), DisplayError> {
+        self.inner.reset_scroll()
+    }
+}
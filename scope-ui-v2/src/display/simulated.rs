@@ -0,0 +1,255 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use display_interface::DisplayError;
+use embedded_graphics::{
+    pixelcolor::{Rgb565, Rgb888},
+    prelude::*,
+};
+use log::error;
+
+use super::{
+    Flushable, PanelOrientation, PanelPower, Scroller, VerticalScroll,
+    ili9341::{Mode, Orientation},
+};
+
+/// Display drawing into memory, to run the UI without hardware.
+///
+/// If an output file is set, every flush writes the frame to it as binary
+/// PPM image, which most image viewers reload when it changes. Vertical
+/// scrolling works like on the ILI9341, only in portrait orientation.
+pub struct SimulatedPanel {
+    /// Size in landscape orientation
+    resolution: Size,
+    orientation: Orientation,
+    /// Frame memory, drawn to without the scroll offset
+    frame: Vec<Rgb565>,
+    scroll: Option<ScrollArea>,
+    output: Option<PathBuf>,
+    sleeping: bool,
+}
+
+/// Scrolling area defined by [`VerticalScroll::vertical_scroll`]
+#[derive(Debug, Clone, Copy)]
+struct ScrollArea {
+    fixed_top: u16,
+    fixed_bottom: u16,
+    /// Frame memory line shown at the top of the scrolling area
+    top_offset: u16,
+}
+
+impl SimulatedPanel {
+    /// Creates a panel with the landscape `resolution`.
+    pub fn new(resolution: Size, orientation: Orientation) -> Self {
+        Self {
+            resolution,
+            orientation,
+            frame: vec![Rgb565::BLACK; (resolution.width * resolution.height) as usize],
+            scroll: None,
+            output: None,
+            sleeping: false,
+        }
+    }
+
+    /// Writes every flushed frame to `path`.
+    pub fn with_output(mut self, path: impl AsRef<Path>) -> Self {
+        self.output = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Color of the pixel shown at `point`, `None` outside of the display.
+    pub fn pixel(&self, point: Point) -> Option<Rgb565> {
+        self.bounding_box()
+            .contains(point)
+            .then(|| self.shown_rows().nth(point.y as usize).unwrap()[point.x as usize])
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Encodes the frame as binary PPM image.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let size = self.size();
+        let mut ppm = format!("P6\n{} {}\n255\n", size.width, size.height).into_bytes();
+        for color in self.shown_rows().flatten() {
+            let color = Rgb888::from(*color);
+            ppm.extend([color.r(), color.g(), color.b()]);
+        }
+        ppm
+    }
+
+    /// Dumps the frame as text with one character per pixel, `#` for lit
+    /// pixels and `.` for the others. Used for snapshot tests.
+    pub fn to_ascii(&self, lit: impl Fn(Rgb565) -> bool) -> String {
+        let width = self.size().width as usize;
+        let mut ascii = String::with_capacity(self.frame.len() + self.frame.len() / width);
+        for row in self.shown_rows() {
+            ascii.extend(row.iter().map(|color| if lit(*color) { '#' } else { '.' }));
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    fn index(&self, point: Point) -> usize {
+        point.y as usize * self.size().width as usize + point.x as usize
+    }
+
+    /// Rows of the frame memory in the order they are shown, taking the
+    /// scroll offset into account.
+    fn shown_rows(&self) -> impl Iterator<Item = &[Rgb565]> {
+        let Size { width, height } = self.size();
+        let rows: Vec<_> = self.frame.chunks(width as usize).collect();
+        (0..height as u16).map(move |y| {
+            let Some(area) = self.scroll else {
+                return rows[y as usize];
+            };
+            let bottom = height as u16 - area.fixed_bottom;
+            if y < area.fixed_top || y >= bottom {
+                return rows[y as usize];
+            }
+            let lines = bottom - area.fixed_top;
+            let shifted = (y - area.fixed_top) + (area.top_offset - area.fixed_top);
+            rows[(area.fixed_top + shifted % lines) as usize]
+        })
+    }
+}
+
+impl Flushable for SimulatedPanel {
+    fn flush(&mut self) -> Result<(), DisplayError> {
+        if let Some(path) = &self.output
+            && let Err(e) = fs::write(path, self.to_ppm())
+        {
+            error!("failed to write frame to {}: {}", path.display(), e);
+            return Err(DisplayError::BusWriteError);
+        }
+        Ok(())
+    }
+}
+
+impl PanelPower for SimulatedPanel {
+    fn set_sleep(&mut self, sleep: bool) -> Result<(), DisplayError> {
+        self.sleeping = sleep;
+        Ok(())
+    }
+}
+
+impl PanelOrientation for SimulatedPanel {
+    fn set_orientation(&mut self, orientation: Orientation) -> Result<(), DisplayError> {
+        self.orientation = orientation;
+        self.frame.fill(Rgb565::BLACK);
+        self.scroll = None;
+        Ok(())
+    }
+}
+
+impl VerticalScroll for SimulatedPanel {
+    /// Scrolls like the ILI9341, only in portrait orientation.
+    fn can_scroll(&self, orientation: Orientation) -> bool {
+        !orientation.is_landscape()
+    }
+
+    fn vertical_scroll(
+        &mut self,
+        fixed_top: u16,
+        fixed_bottom: u16,
+    ) -> Result<Option<Scroller>, DisplayError> {
+        if !self.can_scroll(self.orientation) {
+            return Ok(None);
+        }
+        self.scroll = Some(ScrollArea {
+            fixed_top,
+            fixed_bottom,
+            top_offset: fixed_top,
+        });
+        let height = self.size().height as u16;
+        Ok(Some(Scroller::new(fixed_top, fixed_bottom, height)))
+    }
+
+    fn scroll(&mut self, scroller: &mut Scroller, lines: u16) -> Result<(), DisplayError> {
+        scroller.top_offset += lines;
+        if scroller.top_offset >= scroller.height - scroller.fixed_bottom_lines {
+            scroller.top_offset = scroller.fixed_top_lines
+                + (scroller.top_offset + scroller.fixed_bottom_lines - scroller.height);
+        }
+        if let Some(area) = &mut self.scroll {
+            area.top_offset = scroller.top_offset;
+        }
+        Ok(())
+    }
+
+    fn reset_scroll(&mut self) -> Result<(), DisplayError> {
+        self.scroll = None;
+        Ok(())
+    }
+}
+
+impl OriginDimensions for SimulatedPanel {
+    fn size(&self) -> Size {
+        if self.orientation.is_landscape() {
+            self.resolution
+        } else {
+            Size::new(self.resolution.height, self.resolution.width)
+        }
+    }
+}
+
+impl DrawTarget for SimulatedPanel {
+    type Color = Rgb565;
+
+    type Error = DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounding_box.contains(point) {
+                let idx = self.index(point);
+                self.frame[idx] = color;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.frame.fill(color);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+    use super::*;
+
+    #[test]
+    fn test_draw_and_encode() {
+        let mut display = SimulatedPanel::new(Size::new(4, 2), Orientation::Landscape);
+        Rectangle::new(Point::new(1, 0), Size::new(2, 1))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
+            .draw(&mut display)
+            .unwrap();
+
+        assert_eq!(display.pixel(Point::new(1, 0)), Some(Rgb565::RED));
+        assert_eq!(display.pixel(Point::new(1, 1)), Some(Rgb565::BLACK));
+        assert_eq!(display.pixel(Point::new(4, 0)), None);
+
+        let ppm = display.to_ppm();
+        assert!(ppm.starts_with(b"P6\n4 2\n255\n"));
+        assert_eq!(&ppm[11..17], [0, 0, 0, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_portrait_swaps_size() {
+        let mut display = SimulatedPanel::new(Size::new(320, 240), Orientation::Landscape);
+        display.set_orientation(Orientation::Portrait).unwrap();
+
+        assert_eq!(display.size(), Size::new(240, 320));
+        assert_eq!(display.pixel(Point::new(239, 319)), Some(Rgb565::BLACK));
+    }
+}
@@ -0,0 +1,309 @@
+use display_interface::DisplayError;
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use embedded_hal::i2c::I2c;
+
+use super::{
+    Flushable, PanelOrientation, PanelPower, VerticalScroll,
+    ili9341::{Mode, Orientation},
+};
+
+type Result<T = (), E = DisplayError> = core::result::Result<T, E>;
+
+/// Default I²C address of the SSD1306 modules
+pub const SSD1306_ADDRESS: u8 = 0x3c;
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+const PAGES: usize = HEIGHT / 8;
+
+/// Control byte announcing a command stream
+const CONTROL_COMMAND: u8 = 0x00;
+/// Control byte announcing a data stream
+const CONTROL_DATA: u8 = 0x40;
+/// Number of data bytes sent per I²C transaction
+const CHUNK_SIZE: usize = 64;
+
+/// Driver for 128x64 SSD1306 OLED displays connected over I²C.
+///
+/// Pixels are drawn into a frame buffer in memory, which is transferred to the
+/// display with [`Flushable::flush`]. Each byte of the buffer holds a column of
+/// eight vertical pixels of one page, as expected by the controller in
+/// horizontal addressing mode.
+///
+/// In portrait orientation the drawn pixels are rotated into the frame buffer,
+/// the controller itself only supports flipping the output.
+pub struct Ssd1306<I2C> {
+    i2c: I2C,
+    address: u8,
+    portrait: bool,
+    buffer: [u8; WIDTH * PAGES],
+}
+
+impl<I2C> Ssd1306<I2C>
+where
+    I2C: I2c,
+{
+    /// Initializes the display. `flipped` rotates the output by 180 degree.
+    pub fn new(i2c: I2C, address: u8, flipped: bool) -> Result<Self> {
+        let mut ssd1306 = Ssd1306 {
+            i2c,
+            address,
+            portrait: false,
+            buffer: [0; WIDTH * PAGES],
+        };
+
+        ssd1306.command(Command::DisplayOff, &[])?;
+        ssd1306.command(Command::ClockDivide, &[0x80])?;
+        ssd1306.command(Command::MultiplexRatio, &[HEIGHT as u8 - 1])?;
+        ssd1306.command(Command::DisplayOffset, &[0x00])?;
+        ssd1306.command(Command::StartLine, &[])?;
+        // Enable the internal charge pump
+        ssd1306.command(Command::ChargePump, &[0x14])?;
+        ssd1306.command(Command::AddressingMode, &[0x00])?;
+        ssd1306.set_flipped(flipped)?;
+        ssd1306.command(Command::ComPins, &[0x12])?;
+        ssd1306.command(Command::Contrast, &[0xcf])?;
+        ssd1306.command(Command::PreCharge, &[0xf1])?;
+        ssd1306.command(Command::VcomDeselect, &[0x40])?;
+        ssd1306.command(Command::ResumeToRam, &[])?;
+        ssd1306.command(Command::NormalDisplay, &[])?;
+
+        ssd1306.flush()?;
+        ssd1306.command(Command::DisplayOn, &[])?;
+
+        Ok(ssd1306)
+    }
+
+    fn command(&mut self, cmd: Command, args: &[u8]) -> Result {
+        let mut bytes = [0u8; 4];
+        bytes[0] = CONTROL_COMMAND;
+        bytes[1] = cmd as u8;
+        bytes[2..2 + args.len()].copy_from_slice(args);
+
+        self.i2c
+            .write(self.address, &bytes[..2 + args.len()])
+            .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Rotates the output by 180 degree, takes effect with the next flush.
+    pub fn set_flipped(&mut self, flipped: bool) -> Result {
+        let (segment_remap, com_scan) = if flipped {
+            (Command::SegmentRemapOff, Command::ComScanIncrement)
+        } else {
+            (Command::SegmentRemapOn, Command::ComScanDecrement)
+        };
+        self.command(segment_remap, &[])?;
+        self.command(com_scan, &[])
+    }
+
+    /// Set the contrast of the display to a value between 0 and 255
+    pub fn contrast(&mut self, contrast: u8) -> Result {
+        self.command(Command::Contrast, &[contrast])
+    }
+}
+
+impl<I2C> Ssd1306<I2C> {
+    /// Set a single pixel in the frame buffer
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let idx = (y / 8) * WIDTH + x;
+        let bit = 1 << (y % 8);
+        if on {
+            self.buffer[idx] |= bit;
+        } else {
+            self.buffer[idx] &= !bit;
+        }
+    }
+}
+
+impl<I2C> Flushable for Ssd1306<I2C>
+where
+    I2C: I2c,
+{
+    fn flush(&mut self) -> Result {
+        self.command(Command::ColumnAddress, &[0, WIDTH as u8 - 1])?;
+        self.command(Command::PageAddress, &[0, PAGES as u8 - 1])?;
+
+        let mut chunk = [0u8; CHUNK_SIZE + 1];
+        chunk[0] = CONTROL_DATA;
+        for data in self.buffer.chunks(CHUNK_SIZE) {
+            chunk[1..=data.len()].copy_from_slice(data);
+            self.i2c
+                .write(self.address, &chunk[..=data.len()])
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+        Ok(())
+    }
+}
+
+impl<I2C> PanelPower for Ssd1306<I2C>
+where
+    I2C: I2c,
+{
+    fn set_sleep(&mut self, sleep: bool) -> Result {
+        if sleep {
+            self.command(Command::DisplayOff, &[])
+        } else {
+            self.command(Command::DisplayOn, &[])
+        }
+    }
+}
+
+impl<I2C> PanelOrientation for Ssd1306<I2C>
+where
+    I2C: I2c,
+{
+    fn set_orientation(&mut self, orientation: Orientation) -> Result {
+        let flipped = matches!(
+            orientation,
+            Orientation::LandscapeFlipped | Orientation::PortraitFlipped
+        );
+        self.set_flipped(flipped)?;
+        self.portrait = !orientation.is_landscape();
+        self.buffer.fill(0);
+        Ok(())
+    }
+}
+
+/// The frame buffer is small enough to be redrawn instead of scrolled.
+impl<I2C> VerticalScroll for Ssd1306<I2C> {}
+
+impl<I2C> OriginDimensions for Ssd1306<I2C> {
+    fn size(&self) -> Size {
+        if self.portrait {
+            Size::new(HEIGHT as u32, WIDTH as u32)
+        } else {
+            Size::new(WIDTH as u32, HEIGHT as u32)
+        }
+    }
+}
+
+impl<I2C> DrawTarget for Ssd1306<I2C> {
+    type Color = BinaryColor;
+
+    type Error = DisplayError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if !self.bounding_box().contains(point) {
+                continue;
+            }
+            let (x, y) = (point.x as usize, point.y as usize);
+            if self.portrait {
+                self.set_pixel(y, HEIGHT - 1 - x, color.is_on());
+            } else {
+                self.set_pixel(x, y, color.is_on());
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.buffer.fill(if color.is_on() { 0xff } else { 0x00 });
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Command {
+    AddressingMode = 0x20,
+    ColumnAddress = 0x21,
+    PageAddress = 0x22,
+    StartLine = 0x40,
+    Contrast = 0x81,
+    ChargePump = 0x8d,
+    SegmentRemapOff = 0xa0,
+    SegmentRemapOn = 0xa1,
+    ResumeToRam = 0xa4,
+    NormalDisplay = 0xa6,
+    MultiplexRatio = 0xa8,
+    DisplayOff = 0xae,
+    DisplayOn = 0xaf,
+    ComScanIncrement = 0xc0,
+    ComScanDecrement = 0xc8,
+    DisplayOffset = 0xd3,
+    ClockDivide = 0xd5,
+    PreCharge = 0xd9,
+    ComPins = 0xda,
+    VcomDeselect = 0xdb,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::i2c::{ErrorType, Operation};
+
+    #[derive(Default)]
+    struct DummyI2c {
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl ErrorType for DummyI2c {
+        type Error = Infallible;
+    }
+
+    impl I2c for DummyI2c {
+        fn transaction(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                if let Operation::Write(bytes) = op {
+                    self.writes.push(bytes.to_vec());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pixels_are_packed_into_pages() {
+        let mut display = Ssd1306::new(DummyI2c::default(), SSD1306_ADDRESS, false).unwrap();
+
+        Pixel(Point::new(3, 0), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(3, 9), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+
+        assert_eq!(display.buffer[3], 0x01);
+        assert_eq!(display.buffer[WIDTH + 3], 0x02);
+    }
+
+    #[test]
+    fn test_portrait_rotates_into_buffer() {
+        let mut display = Ssd1306::new(DummyI2c::default(), SSD1306_ADDRESS, false).unwrap();
+        display.set_orientation(Orientation::Portrait).unwrap();
+        assert_eq!(display.size(), Size::new(64, 128));
+
+        Pixel(Point::new(0, 100), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+
+        // left column of the portrait view is the bottom row of the panel
+        assert_eq!(display.buffer[(PAGES - 1) * WIDTH + 100], 0x80);
+    }
+
+    #[test]
+    fn test_flush_sends_whole_buffer() {
+        let mut display = Ssd1306::new(DummyI2c::default(), SSD1306_ADDRESS, false).unwrap();
+        display.clear(BinaryColor::On).unwrap();
+        display.i2c.writes.clear();
+
+        display.flush().unwrap();
+
+        let data: Vec<u8> = display
+            .i2c
+            .writes
+            .iter()
+            .filter(|w| w[0] == CONTROL_DATA)
+            .flat_map(|w| w[1..].to_vec())
+            .collect();
+        assert_eq!(data, vec![0xff; WIDTH * PAGES]);
+    }
+}
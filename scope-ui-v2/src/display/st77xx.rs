@@ -0,0 +1,450 @@
+use display_interface::DataFormat;
+use display_interface::DisplayError;
+use display_interface::WriteOnlyDataCommand;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+use super::ili9341::{DisplaySize, Mode, ModeState, Orientation};
+use super::{Flushable, PanelOrientation, PanelPower, VerticalScroll};
+
+type Result<T = (), E = DisplayError> = core::result::Result<T, E>;
+
+/// Memory access control bits shared by the ST77xx controllers
+const MADCTL_MY: u8 = 0x80;
+const MADCTL_MX: u8 = 0x40;
+const MADCTL_MV: u8 = 0x20;
+const MADCTL_BGR: u8 = 0x08;
+
+/// Display size of the square 1.3"/1.54" ST7789 panels
+pub struct DisplaySize240x240;
+
+impl DisplaySize for DisplaySize240x240 {
+    const WIDTH: usize = 240;
+    const HEIGHT: usize = 240;
+}
+
+/// Display size of the 1.8" ST7735 panels
+pub struct DisplaySize128x160;
+
+impl DisplaySize for DisplaySize128x160 {
+    const WIDTH: usize = 128;
+    const HEIGHT: usize = 160;
+}
+
+/// Supported controllers of the ST77xx family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// ST7735(R) with a 128x160 frame memory, BGR color order
+    St7735,
+    /// ST7789(V) with a 240x320 frame memory, RGB color order and inverted colors
+    St7789,
+}
+
+impl Model {
+    /// Size of the controller frame memory in its native portrait orientation
+    fn ram_size(&self) -> (u16, u16) {
+        match self {
+            Self::St7735 => (128, 160),
+            Self::St7789 => (240, 320),
+        }
+    }
+
+    fn madctl(&self, mode: u8) -> u8 {
+        match self {
+            Self::St7735 => mode | MADCTL_BGR,
+            Self::St7789 => mode & !MADCTL_BGR,
+        }
+    }
+}
+
+/// Driver for ST7735 and ST7789 based TFT displays.
+///
+/// Both controllers share the MIPI DCS command set of the ILI9341, so drawing
+/// works the same way: a window is set up and filled with rgb565 pixel values.
+///
+/// Panels which are smaller than the frame memory of the controller (e.g. the
+/// 240x240 ST7789 modules) need an offset for the address window depending on
+/// the orientation, which is handled by the driver.
+pub struct St77xx<IFACE, RESET> {
+    interface: IFACE,
+    reset: RESET,
+    model: Model,
+    panel_size: (u16, u16),
+    width: usize,
+    height: usize,
+    landscape: bool,
+    offset: (u16, u16),
+}
+
+impl<IFACE, RESET> St77xx<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+    RESET: OutputPin,
+{
+    pub fn new<DELAY, SIZE, MODE>(
+        interface: IFACE,
+        reset: RESET,
+        delay: &mut DELAY,
+        model: Model,
+        mode: MODE,
+        _display_size: SIZE,
+    ) -> Result<Self>
+    where
+        DELAY: DelayNs,
+        SIZE: DisplaySize,
+        MODE: Mode,
+    {
+        let mut st77xx = St77xx {
+            interface,
+            reset,
+            model,
+            panel_size: (SIZE::WIDTH as u16, SIZE::HEIGHT as u16),
+            width: SIZE::WIDTH,
+            height: SIZE::HEIGHT,
+            landscape: false,
+            offset: (0, 0),
+        };
+
+        // Do hardware reset by holding reset low for at least 10us
+        st77xx.reset.set_low().map_err(|_| DisplayError::RSError)?;
+        delay.delay_ms(1);
+        // Set high for normal operation
+        st77xx.reset.set_high().map_err(|_| DisplayError::RSError)?;
+        delay.delay_ms(5);
+
+        // Do software reset and wait 150ms before sending Sleep Out
+        st77xx.command(Command::SoftwareReset, &[])?;
+        delay.delay_ms(150);
+
+        st77xx.sleep_mode(ModeState::Off)?;
+        // Wait 120ms after Sleep Out before sending commands
+        delay.delay_ms(120);
+
+        match model {
+            Model::St7735 => {
+                st77xx.command(Command::FrameRateNormal, &[0x01, 0x2c, 0x2d])?;
+                st77xx.command(Command::FrameRateIdle, &[0x01, 0x2c, 0x2d])?;
+                st77xx.command(
+                    Command::FrameRatePartial,
+                    &[0x01, 0x2c, 0x2d, 0x01, 0x2c, 0x2d],
+                )?;
+                st77xx.command(Command::InversionControl, &[0x07])?;
+                st77xx.command(Command::PowerControl1, &[0xa2, 0x02, 0x84])?;
+                st77xx.command(Command::PowerControl2, &[0xc5])?;
+                st77xx.command(Command::PowerControl3, &[0x0a, 0x00])?;
+                st77xx.command(Command::PowerControl4, &[0x8a, 0x2a])?;
+                st77xx.command(Command::PowerControl5, &[0x8a, 0xee])?;
+                st77xx.command(Command::VcomControl, &[0x0e])?;
+                st77xx.command(Command::InvertOff, &[])?;
+            }
+            Model::St7789 => {
+                // The IPS panels used with the ST7789 show inverted colors otherwise
+                st77xx.command(Command::InvertOn, &[])?;
+            }
+        }
+
+        st77xx.set_orientation(mode)?;
+
+        // Set pixel format to 16 bits per pixel
+        st77xx.command(Command::PixelFormatSet, &[0x55])?;
+        st77xx.command(Command::NormalDisplayOn, &[])?;
+        delay.delay_ms(10);
+
+        st77xx.display_mode(ModeState::On)?;
+
+        Ok(st77xx)
+    }
+}
+
+impl<IFACE, RESET> St77xx<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    fn command(&mut self, cmd: Command, args: &[u8]) -> Result {
+        self.interface.send_commands(DataFormat::U8(&[cmd as u8]))?;
+        self.interface.send_data(DataFormat::U8(args))
+    }
+
+    fn write_iter<I: IntoIterator<Item = u16>>(&mut self, data: I) -> Result {
+        self.command(Command::MemoryWrite, &[])?;
+        use DataFormat::U16BEIter;
+        self.interface.send_data(U16BEIter(&mut data.into_iter()))
+    }
+
+    fn write_slice(&mut self, data: &mut [u16]) -> Result {
+        self.command(Command::MemoryWrite, &[])?;
+        self.interface.send_data(DataFormat::U16BE(data))
+    }
+
+    fn set_window(&mut self, x0: u16, y0: u16, x1: u16, y1: u16) -> Result {
+        let (ox, oy) = self.offset;
+        self.command(Command::ColumnAddressSet, &pack_coords(x0 + ox, x1 + ox))?;
+        self.command(Command::PageAddressSet, &pack_coords(y0 + oy, y1 + oy))
+    }
+
+    /// Draw a rectangle on the screen, represented by top-left corner (x0, y0)
+    /// and bottom-right corner (x1, y1).
+    ///
+    /// This method accepts an iterator of rgb565 pixel values.
+    pub fn draw_raw_iter<I: IntoIterator<Item = u16>>(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        data: I,
+    ) -> Result {
+        self.set_window(x0, y0, x1, y1)?;
+        self.write_iter(data)
+    }
+
+    /// Draw a rectangle on the screen, represented by top-left corner (x0, y0)
+    /// and bottom-right corner (x1, y1).
+    ///
+    /// This method accepts a raw buffer of rgb565 pixel values.
+    pub fn draw_raw_slice(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        data: &mut [u16],
+    ) -> Result {
+        self.set_window(x0, y0, x1, y1)?;
+        self.write_slice(data)
+    }
+
+    /// Change the orientation of the screen
+    pub fn set_orientation<MODE>(&mut self, mode: MODE) -> Result
+    where
+        MODE: Mode,
+    {
+        let madctl = self.model.madctl(mode.mode());
+        self.command(Command::MemoryAccessControl, &[madctl])?;
+
+        if self.landscape ^ mode.is_landscape() {
+            core::mem::swap(&mut self.height, &mut self.width);
+        }
+        self.landscape = mode.is_landscape();
+        self.offset = self.window_offset(madctl);
+        Ok(())
+    }
+
+    /// Mirrored axes start at the end of the frame memory, so a panel smaller
+    /// than the memory needs an offset on these axes.
+    fn window_offset(&self, madctl: u8) -> (u16, u16) {
+        let (ram_width, ram_height) = self.model.ram_size();
+        let (panel_width, panel_height) = self.panel_size;
+
+        let column = if madctl & MADCTL_MX != 0 {
+            ram_width - panel_width
+        } else {
+            0
+        };
+        let row = if madctl & MADCTL_MY != 0 {
+            ram_height - panel_height
+        } else {
+            0
+        };
+
+        if madctl & MADCTL_MV != 0 {
+            (row, column)
+        } else {
+            (column, row)
+        }
+    }
+
+    /// Fill entire screen with specfied color u16 value
+    pub fn clear_screen(&mut self, color: u16) -> Result {
+        let color = core::iter::repeat_n(color, self.width * self.height);
+        self.draw_raw_iter(0, 0, self.width as u16 - 1, self.height as u16 - 1, color)
+    }
+
+    /// Control the screen sleep mode
+    pub fn sleep_mode(&mut self, mode: ModeState) -> Result {
+        match mode {
+            ModeState::On => self.command(Command::SleepModeOn, &[]),
+            ModeState::Off => self.command(Command::SleepModeOff, &[]),
+        }
+    }
+
+    /// Control the screen display mode
+    pub fn display_mode(&mut self, mode: ModeState) -> Result {
+        match mode {
+            ModeState::On => self.command(Command::DisplayOn, &[]),
+            ModeState::Off => self.command(Command::DisplayOff, &[]),
+        }
+    }
+}
+
+impl<IFACE, RESET> Flushable for St77xx<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    /// Pixels are written to the display immediately, there is nothing to flush.
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+impl<IFACE, RESET> PanelPower for St77xx<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    fn set_sleep(&mut self, sleep: bool) -> Result {
+        if sleep {
+            self.display_mode(ModeState::Off)?;
+            self.sleep_mode(ModeState::On)
+        } else {
+            self.sleep_mode(ModeState::Off)?;
+            // Wait 120ms after Sleep Out before sending commands
+            std::thread::sleep(std::time::Duration::from_millis(120));
+            self.display_mode(ModeState::On)
+        }
+    }
+}
+
+impl<IFACE, RESET> PanelOrientation for St77xx<IFACE, RESET>
+where
+    IFACE: WriteOnlyDataCommand,
+{
+    fn set_orientation(&mut self, orientation: Orientation) -> Result {
+        St77xx::set_orientation(self, orientation)
+    }
+}
+
+/// Hardware scrolling is not supported yet, the frame memory of the ST7789 is
+/// larger than the common panels which needs an extra offset.
+impl<IFACE, RESET> VerticalScroll for St77xx<IFACE, RESET> {}
+
+impl<IFACE, RESET> St77xx<IFACE, RESET> {
+    /// Get the current screen width. It can change based on the current orientation
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the current screen height. It can change based on the current orientation
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the controller model
+    pub fn model(&self) -> Model {
+        self.model
+    }
+}
+
+fn pack_coords(start: u16, end: u16) -> [u8; 4] {
+    [
+        (start >> 8) as u8,
+        (start & 0xff) as u8,
+        (end >> 8) as u8,
+        (end & 0xff) as u8,
+    ]
+}
+
+#[derive(Clone, Copy)]
+enum Command {
+    SoftwareReset = 0x01,
+    SleepModeOn = 0x10,
+    SleepModeOff = 0x11,
+    NormalDisplayOn = 0x13,
+    InvertOff = 0x20,
+    InvertOn = 0x21,
+    DisplayOff = 0x28,
+    DisplayOn = 0x29,
+    ColumnAddressSet = 0x2a,
+    PageAddressSet = 0x2b,
+    MemoryWrite = 0x2c,
+    MemoryAccessControl = 0x36,
+    PixelFormatSet = 0x3a,
+    FrameRateNormal = 0xb1,
+    FrameRateIdle = 0xb2,
+    FrameRatePartial = 0xb3,
+    InversionControl = 0xb4,
+    PowerControl1 = 0xc0,
+    PowerControl2 = 0xc1,
+    PowerControl3 = 0xc2,
+    PowerControl4 = 0xc3,
+    PowerControl5 = 0xc4,
+    VcomControl = 0xc5,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::ili9341::Orientation;
+    use core::convert::Infallible;
+    use embedded_hal::digital::ErrorType;
+
+    struct DummyDelay;
+
+    impl DelayNs for DummyDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    struct DummyPin;
+
+    impl ErrorType for DummyPin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for DummyPin {
+        fn set_low(&mut self) -> core::result::Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> core::result::Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    struct DummyInterface;
+
+    impl WriteOnlyDataCommand for DummyInterface {
+        fn send_commands(&mut self, _cmd: DataFormat<'_>) -> Result {
+            Ok(())
+        }
+
+        fn send_data(&mut self, _buf: DataFormat<'_>) -> Result {
+            Ok(())
+        }
+    }
+
+    fn st7789(orientation: Orientation) -> St77xx<DummyInterface, DummyPin> {
+        St77xx::new(
+            DummyInterface,
+            DummyPin,
+            &mut DummyDelay,
+            Model::St7789,
+            orientation,
+            DisplaySize240x240,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_st7789_window_offset() {
+        assert_eq!(st7789(Orientation::Portrait).offset, (0, 0));
+        assert_eq!(st7789(Orientation::PortraitFlipped).offset, (0, 80));
+        assert_eq!(st7789(Orientation::Landscape).offset, (0, 0));
+        assert_eq!(st7789(Orientation::LandscapeFlipped).offset, (80, 0));
+    }
+
+    #[test]
+    fn test_orientation_swaps_size() {
+        let mut display = St77xx::new(
+            DummyInterface,
+            DummyPin,
+            &mut DummyDelay,
+            Model::St7735,
+            Orientation::Portrait,
+            DisplaySize128x160,
+        )
+        .unwrap();
+        assert_eq!((display.width(), display.height()), (128, 160));
+
+        display.set_orientation(Orientation::Landscape).unwrap();
+        assert_eq!((display.width(), display.height()), (160, 128));
+    }
+}
@@ -0,0 +1,141 @@
+//! Stage positions after the last moves, to move back along them.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::client::OpenFlexurePosition;
+
+/// Most positions kept, older ones are dropped
+const MAX_POSITIONS: usize = 50;
+/// Pause after which turning the encoder again starts a new entry
+const BURST_GAP: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    position: OpenFlexurePosition,
+    recorded: Instant,
+    /// Reached by a step of the encoder or the joystick
+    burst: bool,
+}
+
+/// Bounded history of stage positions, the last one is the current position.
+///
+/// Steps of the encoder or the joystick which follow each other quickly are
+/// one entry, so undoing an accidental spin goes back to where it started.
+#[derive(Debug, Default)]
+pub struct PositionHistory {
+    entries: VecDeque<Entry>,
+}
+
+impl PositionHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the position after a move. `burst` marks moves of the encoder
+    /// or the joystick, which are merged into the previous entry if it was
+    /// recorded shortly before.
+    pub fn record(&mut self, position: OpenFlexurePosition, burst: bool, now: Instant) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.position == position {
+                last.recorded = now;
+                return;
+            }
+            if burst && last.burst && now.duration_since(last.recorded) < BURST_GAP {
+                *last = Entry {
+                    position,
+                    recorded: now,
+                    burst,
+                };
+                return;
+            }
+        }
+
+        if self.entries.len() >= MAX_POSITIONS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            position,
+            recorded: now,
+            burst,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of positions to go back to
+    pub fn undo_steps(&self) -> usize {
+        self.entries.len().saturating_sub(1)
+    }
+
+    /// Drops the current position and returns the one before it.
+    pub fn undo(&mut self) -> Option<OpenFlexurePosition> {
+        if self.undo_steps() == 0 {
+            return None;
+        }
+        self.entries.pop_back();
+        self.entries.back().map(|entry| entry.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(x: i64) -> OpenFlexurePosition {
+        OpenFlexurePosition { x, y: 0, z: 0 }
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut history = PositionHistory::new();
+        let now = Instant::now();
+        assert_eq!(history.undo(), None);
+
+        history.record(position(0), false, now);
+        history.record(position(100), false, now);
+        history.record(position(100), false, now);
+        history.record(position(200), false, now);
+        assert_eq!(history.undo_steps(), 2);
+
+        assert_eq!(history.undo(), Some(position(100)));
+        // reaching the position again does not add it
+        history.record(position(100), false, now);
+        assert_eq!(history.undo(), Some(position(0)));
+        assert_eq!(history.undo(), None);
+    }
+
+    #[test]
+    fn test_bursts_are_merged() {
+        let mut history = PositionHistory::new();
+        let start = Instant::now();
+        history.record(position(0), false, start);
+        for step in 1..=5 {
+            history.record(
+                position(step * 10),
+                true,
+                start + step as u32 * BURST_GAP / 4,
+            );
+        }
+        assert_eq!(history.undo_steps(), 1);
+
+        // a pause starts a new entry
+        history.record(position(500), true, start + BURST_GAP * 4);
+        assert_eq!(history.undo(), Some(position(50)));
+        assert_eq!(history.undo(), Some(position(0)));
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = PositionHistory::new();
+        let now = Instant::now();
+        for x in 0..100 {
+            history.record(position(x), false, now);
+        }
+        assert_eq!(history.undo_steps(), MAX_POSITIONS - 1);
+    }
+}
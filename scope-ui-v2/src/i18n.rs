@@ -0,0 +1,143 @@
+//! Translations of the texts shown on the display.
+//!
+//! The UI is written in English, the English text is looked up in the table
+//! of the selected language. Texts without translation are shown in English.
+
+use serde::{Deserialize, Serialize};
+
+/// Language of the texts on the display, selected on the settings screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+/// English text and its German translation. The panels show about 20
+/// characters per line, so the translations have to stay short.
+const GERMAN: &[(&str, &str)] = &[
+    ("X Axis", "X-Achse"),
+    ("Y Axis", "Y-Achse"),
+    ("Z Axis", "Z-Achse"),
+    ("Slider", "Schieber"),
+    ("Settings", "Einstellungen"),
+    ("Control Mode", "Steuermodus"),
+    ("On", "An"),
+    ("Off", "Aus"),
+    ("Back", "Zurück"),
+    ("Step", "Schritt"),
+    ("Fine step", "Feinschritt"),
+    ("Server", "Server"),
+    ("Custom", "Eigene"),
+    ("Brightness", "Helligkeit"),
+    ("Sleep after", "Ruhe nach"),
+    ("Rotate", "Drehen"),
+    ("Land", "Quer"),
+    ("Land 180", "Quer 180"),
+    ("Port", "Hoch"),
+    ("Port 180", "Hoch 180"),
+    ("Invert enc.", "Enc. umkehren"),
+    ("Language", "Sprache"),
+    ("offline", "offline"),
+    ("Board device", "Board-Gerät"),
+    ("present", "vorhanden"),
+    ("missing", "fehlt"),
+    ("Uptime", "Laufzeit"),
+    ("CPU temp", "CPU-Temp."),
+    ("SD free", "SD frei"),
+    ("Position reached", "Position erreicht"),
+    ("Out of range", "Außer Bereich"),
+    ("Go to", "Gehe zu"),
+    ("Move", "Fahren"),
+    ("Moving", "Fährt"),
+    ("Stopped", "Angehalten"),
+    ("Emergency stop", "Not-Halt"),
+    ("No bookmarks", "Keine Lesezeichen"),
+    ("Batch too long", "Serie zu lang"),
+    ("X target", "X-Ziel"),
+    ("Y target", "Y-Ziel"),
+    ("Z target", "Z-Ziel"),
+    ("Bookmarks", "Lesezeichen"),
+    ("Save here", "Hier sichern"),
+    ("Save as", "Sichern als"),
+    ("Saved", "Gesichert"),
+    ("Action", "Aktion"),
+    ("Delete", "Löschen"),
+    ("Limits", "Grenzen"),
+    ("Mode", "Modus"),
+    ("Clamp", "Begrenzen"),
+    ("Reject", "Ablehnen"),
+    ("Clear", "Leeren"),
+    ("Clear all limits?", "Alle Grenzen leeren?"),
+    ("Jog to 1st end", "Zum 1. Ende"),
+    ("Jog to 2nd end", "Zum 2. Ende"),
+    ("Outside of limits", "Außerhalb Grenzen"),
+    ("Unit", "Einheit"),
+    ("Calibration", "Kalibrierung"),
+    ("Calibrate XY", "XY kalibrieren"),
+    ("X steps/mm", "X Schritte/mm"),
+    ("Y steps/mm", "Y Schritte/mm"),
+    ("Z steps/mm", "Z Schritte/mm"),
+    ("nm/pixel", "nm/Pixel"),
+    ("µm/pixel", "µm/Pixel"),
+    ("Set µm/pixel first", "Erst µm/Pixel setzen"),
+    ("Calibrated", "Kalibriert"),
+    ("Calibration failed", "Fehler bei Kalibrierung"),
+    ("Undo", "Zurück fahren"),
+    ("No previous position", "Keine vorige Position"),
+];
+
+impl Language {
+    /// Name of the language in the language itself
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::German => "Deutsch",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::English => Self::German,
+            Self::German => Self::English,
+        }
+    }
+
+    /// Translates an English text of the UI into the language.
+    pub fn translate(self, text: &'static str) -> &'static str {
+        let table = match self {
+            Self::English => return text,
+            Self::German => GERMAN,
+        };
+
+        table
+            .iter()
+            .find(|(english, _)| *english == text)
+            .map_or(text, |(_, translation)| translation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(Language::English.translate("Back"), "Back");
+        assert_eq!(Language::German.translate("Back"), "Zurück");
+        // missing translations fall back to English
+        assert_eq!(Language::German.translate("Log"), "Log");
+    }
+
+    #[test]
+    fn test_translations_are_unique() {
+        for (idx, (english, _)) in GERMAN.iter().enumerate() {
+            assert!(
+                !GERMAN[idx + 1..].iter().any(|(other, _)| other == english),
+                "{} is translated twice",
+                english
+            );
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::time::{Duration, Instant};
+
+use embedded_hal::digital::InputPin;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{InputEvent, MenuInput};
+
+/// Actions which can be bound to buttons and gestures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Captures an image at every bookmark
+    StartBatch,
+    /// Drops the queued moves, the running move finishes
+    Stop,
+    Capture,
+    /// Cancels the running move as well
+    EmergencyRelease,
+    Home,
+}
+
+/// Gestures of the encoder button which can be bound to an action instead of
+/// their default function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gesture {
+    Click,
+    DoubleClick,
+    LongPress,
+}
+
+impl Gesture {
+    fn of(event: InputEvent) -> Option<Self> {
+        match event {
+            InputEvent::Select => Some(Self::Click),
+            InputEvent::DoubleClick => Some(Self::DoubleClick),
+            InputEvent::LongPress => Some(Self::LongPress),
+            _ => None,
+        }
+    }
+}
+
+/// Input triggering a bound action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// Button with the given name from the
+    /// [`InputConfig::buttons`](super::InputConfig::buttons)
+    Button(String),
+    Gesture(Gesture),
+}
+
+/// Entry of the action binding table, e.g.
+/// `{ "button": "start", "action": "start_batch" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    #[serde(flatten)]
+    pub trigger: Trigger,
+    pub action: Action,
+}
+
+/// Returns the action bound to `trigger`.
+pub fn bound_action(bindings: &[Binding], trigger: &Trigger) -> Option<Action> {
+    bindings
+        .iter()
+        .find(|binding| binding.trigger == *trigger)
+        .map(|binding| binding.action)
+}
+
+/// Replaces gesture events with the actions bound to them.
+pub fn apply_bindings(bindings: &[Binding], event: InputEvent) -> InputEvent {
+    Gesture::of(event)
+        .and_then(|gesture| bound_action(bindings, &Trigger::Gesture(gesture)))
+        .map_or(event, InputEvent::Action)
+}
+
+/// Looks up the action of the button called `name`, warning about buttons
+/// without binding.
+pub fn button_action(bindings: &[Binding], name: &str) -> Option<Action> {
+    let action = bound_action(bindings, &Trigger::Button(name.to_string()));
+    if action.is_none() {
+        warn!("no action bound to button {}", name);
+    }
+    action
+}
+
+/// GPIO button, part of the [`InputConfig`](super::InputConfig).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ButtonConfig {
+    /// Name used in the bindings
+    pub name: String,
+    /// BCM number of the GPIO pin
+    pub pin: u8,
+    /// The button pulls the pin low when pressed, the pin is pulled up
+    /// otherwise
+    #[serde(default = "default_active_low")]
+    pub active_low: bool,
+}
+
+fn default_active_low() -> bool {
+    true
+}
+
+/// Time the level of a button has to be stable before it is reported
+const BUTTON_DEBOUNCE: Duration = Duration::from_millis(20);
+
+/// Debounced push button triggering an [`Action`] when pressed.
+pub struct Button<P> {
+    pin: P,
+    active_low: bool,
+    action: Action,
+    pressed: bool,
+    /// Level read last and since when it is stable
+    level: (bool, Instant),
+}
+
+impl<P> Button<P>
+where
+    P: InputPin,
+{
+    pub fn new(mut pin: P, active_low: bool, action: Action, now: Instant) -> Self {
+        let level = pin.is_high().unwrap_or(active_low);
+        Self {
+            pin,
+            active_low,
+            action,
+            pressed: level != active_low,
+            level: (level, now),
+        }
+    }
+
+    /// Reads the pin at `now` and returns the action once the button is
+    /// pressed.
+    pub fn sample(&mut self, now: Instant) -> Option<InputEvent> {
+        let level = self.pin.is_high().ok()?;
+        if level != self.level.0 {
+            self.level = (level, now);
+            return None;
+        }
+
+        let pressed = level != self.active_low;
+        if pressed == self.pressed || now.duration_since(self.level.1) < BUTTON_DEBOUNCE {
+            return None;
+        }
+        self.pressed = pressed;
+        if !pressed {
+            return None;
+        }
+
+        debug!("button pressed, {:?}", self.action);
+        Some(InputEvent::Action(self.action))
+    }
+}
+
+impl<P> MenuInput for Button<P>
+where
+    P: InputPin,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        self.sample(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quadrature::mock::{MockClock, Script, ScriptedPin};
+
+    use super::*;
+
+    #[test]
+    fn test_bindings_from_json() {
+        let bindings: Vec<Binding> = serde_json::from_str(
+            r#"[
+                { "button": "start", "action": "start_batch" },
+                { "gesture": "long_press", "action": "emergency_release" }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(button_action(&bindings, "start"), Some(Action::StartBatch));
+        assert_eq!(button_action(&bindings, "stop"), None);
+        assert_eq!(
+            apply_bindings(&bindings, InputEvent::LongPress),
+            InputEvent::Action(Action::EmergencyRelease)
+        );
+        assert_eq!(
+            apply_bindings(&bindings, InputEvent::DoubleClick),
+            InputEvent::DoubleClick
+        );
+    }
+
+    #[test]
+    fn test_bouncy_button_triggers_once() {
+        let ms = Duration::from_millis;
+        let clock = MockClock::new();
+        // active low, bouncing on press and release
+        let script = Script::new(true)
+            .then(ms(10), false)
+            .then(ms(11), true)
+            .then(ms(12), false)
+            .then(ms(100), true)
+            .then(ms(101), false)
+            .then(ms(103), true);
+        let start = Instant::now();
+        let mut button = Button::new(
+            ScriptedPin::new(script, &clock),
+            true,
+            Action::Capture,
+            start,
+        );
+
+        let mut events = Vec::new();
+        while clock.now() < ms(200) {
+            events.extend(button.sample(start + clock.now()));
+            clock.advance(ms(1));
+        }
+
+        assert_eq!(events, [InputEvent::Action(Action::Capture)]);
+    }
+
+    #[test]
+    fn test_active_high_button() {
+        let ms = Duration::from_millis;
+        let clock = MockClock::new();
+        let script = Script::new(false).then(ms(10), true);
+        let start = Instant::now();
+        let mut button = Button::new(ScriptedPin::new(script, &clock), false, Action::Stop, start);
+
+        clock.advance(ms(10));
+        assert_eq!(button.sample(start + clock.now()), None);
+        clock.advance(ms(20));
+        assert_eq!(
+            button.sample(start + clock.now()),
+            Some(InputEvent::Action(Action::Stop))
+        );
+    }
+}
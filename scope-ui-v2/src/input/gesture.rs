@@ -0,0 +1,293 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::{InputEvent, MenuInput};
+
+/// Timings used by the [`GestureRecognizer`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GestureConfig {
+    /// Milliseconds the button has to be held for a long press.
+    pub long_press_ms: u64,
+    /// Milliseconds after a click in which a second click counts as double
+    /// click. Clicks are reported after this time, `0` disables double clicks.
+    pub double_click_ms: u64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            long_press_ms: 800,
+            double_click_ms: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    /// The button is held down. `handled` is set once the press produced an
+    /// event, so releasing it does not count as click.
+    Pressed {
+        since: Instant,
+        handled: bool,
+    },
+    /// The button was clicked once, waiting for a second click.
+    Clicked {
+        at: Instant,
+    },
+}
+
+/// Turns the raw [`InputEvent::ButtonDown`] and [`InputEvent::ButtonUp`]
+/// events into clicks, double clicks, long presses and turns while pressed.
+///
+/// The recognizer does not read the clock itself, every event is fed with its
+/// timestamp and timeouts are checked by [`GestureRecognizer::tick`].
+pub struct GestureRecognizer {
+    long_press: Duration,
+    double_click: Duration,
+    state: State,
+    events: VecDeque<InputEvent>,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: &GestureConfig) -> Self {
+        Self {
+            long_press: Duration::from_millis(config.long_press_ms),
+            double_click: Duration::from_millis(config.double_click_ms),
+            state: State::Idle,
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Feeds an event received at `now` into the recognizer.
+    pub fn feed(&mut self, event: InputEvent, now: Instant) {
+        self.tick(now);
+
+        self.state = match (self.state, event) {
+            (State::Idle, InputEvent::ButtonDown) => State::Pressed {
+                since: now,
+                handled: false,
+            },
+            (State::Clicked { .. }, InputEvent::ButtonDown) => {
+                self.emit(InputEvent::DoubleClick);
+                State::Pressed {
+                    since: now,
+                    handled: true,
+                }
+            }
+            (State::Pressed { handled, .. }, InputEvent::ButtonUp) => {
+                if handled {
+                    State::Idle
+                } else if self.double_click.is_zero() {
+                    self.emit(InputEvent::Select);
+                    State::Idle
+                } else {
+                    State::Clicked { at: now }
+                }
+            }
+            (State::Pressed { since, .. }, InputEvent::Up) => {
+                self.emit(InputEvent::FineUp);
+                State::Pressed {
+                    since,
+                    handled: true,
+                }
+            }
+            (State::Pressed { since, .. }, InputEvent::Down) => {
+                self.emit(InputEvent::FineDown);
+                State::Pressed {
+                    since,
+                    handled: true,
+                }
+            }
+            (State::Clicked { .. }, event) => {
+                // another input ends the wait for a second click
+                self.emit(InputEvent::Select);
+                self.forward(event);
+                State::Idle
+            }
+            (state, event) => {
+                self.forward(event);
+                state
+            }
+        };
+    }
+
+    /// Emits the events which are due at `now`.
+    pub fn tick(&mut self, now: Instant) {
+        match self.state {
+            State::Pressed {
+                since,
+                handled: false,
+            } if now.duration_since(since) >= self.long_press => {
+                self.emit(InputEvent::LongPress);
+                self.state = State::Pressed {
+                    since,
+                    handled: true,
+                };
+            }
+            State::Clicked { at } if now.duration_since(at) >= self.double_click => {
+                self.emit(InputEvent::Select);
+                self.state = State::Idle;
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the next recognized event.
+    pub fn pop(&mut self) -> Option<InputEvent> {
+        self.events.pop_front()
+    }
+
+    /// Forwards events which are not part of a gesture, stray button events
+    /// are dropped.
+    fn forward(&mut self, event: InputEvent) {
+        if !matches!(event, InputEvent::ButtonDown | InputEvent::ButtonUp) {
+            self.events.push_back(event);
+        }
+    }
+
+    fn emit(&mut self, event: InputEvent) {
+        debug!("recognized gesture {:?}", event);
+        self.events.push_back(event);
+    }
+}
+
+/// [`MenuInput`] recognizing gestures on the events of another input.
+pub struct GestureInput<I> {
+    inner: I,
+    recognizer: GestureRecognizer,
+}
+
+impl<I> GestureInput<I> {
+    pub fn new(inner: I, config: &GestureConfig) -> Self {
+        Self {
+            inner,
+            recognizer: GestureRecognizer::new(config),
+        }
+    }
+}
+
+impl<I> MenuInput for GestureInput<I>
+where
+    I: MenuInput,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        let now = Instant::now();
+        match self.inner.poll() {
+            Some(event) => self.recognizer.feed(event, now),
+            None => self.recognizer.tick(now),
+        }
+        self.recognizer.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recognize(events: &[(u64, InputEvent)], end: u64) -> Vec<InputEvent> {
+        let start = Instant::now();
+        let mut recognizer = GestureRecognizer::new(&GestureConfig::default());
+        for (ms, event) in events {
+            recognizer.feed(*event, start + Duration::from_millis(*ms));
+        }
+        recognizer.tick(start + Duration::from_millis(end));
+
+        std::iter::from_fn(|| recognizer.pop()).collect()
+    }
+
+    #[test]
+    fn test_click_is_reported_after_double_click_timeout() {
+        let events = [(0, InputEvent::ButtonDown), (100, InputEvent::ButtonUp)];
+
+        assert_eq!(recognize(&events, 300), vec![]);
+        assert_eq!(recognize(&events, 400), vec![InputEvent::Select]);
+    }
+
+    #[test]
+    fn test_double_click() {
+        let events = [
+            (0, InputEvent::ButtonDown),
+            (100, InputEvent::ButtonUp),
+            (250, InputEvent::ButtonDown),
+            (350, InputEvent::ButtonUp),
+        ];
+
+        assert_eq!(recognize(&events, 2000), vec![InputEvent::DoubleClick]);
+    }
+
+    #[test]
+    fn test_slow_clicks_are_two_clicks() {
+        let events = [
+            (0, InputEvent::ButtonDown),
+            (100, InputEvent::ButtonUp),
+            (500, InputEvent::ButtonDown),
+            (600, InputEvent::ButtonUp),
+        ];
+
+        assert_eq!(
+            recognize(&events, 2000),
+            vec![InputEvent::Select, InputEvent::Select]
+        );
+    }
+
+    #[test]
+    fn test_long_press_fires_while_held() {
+        let events = [(0, InputEvent::ButtonDown)];
+        assert_eq!(recognize(&events, 700), vec![]);
+        assert_eq!(recognize(&events, 800), vec![InputEvent::LongPress]);
+
+        let events = [(0, InputEvent::ButtonDown), (1000, InputEvent::ButtonUp)];
+        assert_eq!(recognize(&events, 2000), vec![InputEvent::LongPress]);
+    }
+
+    #[test]
+    fn test_turn_while_pressed_is_fine_adjust() {
+        let events = [
+            (0, InputEvent::ButtonDown),
+            (100, InputEvent::Up),
+            (200, InputEvent::Down),
+            (1000, InputEvent::ButtonUp),
+            (1100, InputEvent::Up),
+        ];
+
+        assert_eq!(
+            recognize(&events, 2000),
+            vec![InputEvent::FineUp, InputEvent::FineDown, InputEvent::Up]
+        );
+    }
+
+    #[test]
+    fn test_turn_after_click_reports_click_first() {
+        let events = [
+            (0, InputEvent::ButtonDown),
+            (100, InputEvent::ButtonUp),
+            (150, InputEvent::Down),
+        ];
+
+        assert_eq!(
+            recognize(&events, 150),
+            vec![InputEvent::Select, InputEvent::Down]
+        );
+    }
+
+    #[test]
+    fn test_disabled_double_click_reports_click_on_release() {
+        let start = Instant::now();
+        let mut recognizer = GestureRecognizer::new(&GestureConfig {
+            double_click_ms: 0,
+            ..Default::default()
+        });
+
+        recognizer.feed(InputEvent::ButtonDown, start);
+        recognizer.feed(InputEvent::ButtonUp, start + Duration::from_millis(50));
+
+        assert_eq!(recognizer.pop(), Some(InputEvent::Select));
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use log::{debug, error};
+use rppal::gpio::{Event, Gpio, InputPin, Trigger};
+
+use quadrature::{Decoder, Direction};
+
+use super::{EncoderDirection, InputEvent, MenuInput};
+
+/// Debounce time of the button, the encoder signals are debounced by the
+/// quadrature state machine.
+const BUTTON_DEBOUNCE: Duration = Duration::from_millis(5);
+/// Longest wait for an edge of the encoder, rppal can not set up interrupts
+/// of other pins meanwhile
+const EDGE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Rotary encoder reading its pins from edge interrupts.
+///
+/// A thread waits for the edges of both encoder signals and reads both
+/// levels after each edge, so the decoder sees them in order. The button
+/// uses an interrupt callback of rppal. The decoded events are sent into a
+/// channel, so [`MenuInput::poll`] returns at once, instead of busy-polling
+/// the pins.
+pub struct InterruptEncoder {
+    // the interrupt is cleared when the pin is dropped, the encoder pins
+    // belong to the decoding thread
+    _sw: InputPin,
+    events: Receiver<InputEvent>,
+}
+
+impl InterruptEncoder {
+    /// Sets up the interrupts on all pins. Fails if the GPIO character device
+    /// does not support interrupts on them.
+    pub fn new(
+        gpio: &Gpio,
+        mut dt: InputPin,
+        mut clk: InputPin,
+        mut sw: InputPin,
+        decoder: Decoder,
+        direction: EncoderDirection,
+    ) -> rppal::gpio::Result<Self> {
+        let (tx, events) = mpsc::channel();
+        dt.set_interrupt(Trigger::Both, None)?;
+        clk.set_interrupt(Trigger::Both, None)?;
+
+        let button_tx = tx.clone();
+        sw.set_async_interrupt(Trigger::Both, Some(BUTTON_DEBOUNCE), move |event| {
+            // the button pulls the pin low
+            let event = if is_rising(&event) {
+                InputEvent::ButtonUp
+            } else {
+                InputEvent::ButtonDown
+            };
+            debug!("rotary encoder {:?}", event);
+            let _ = button_tx.send(event);
+        })?;
+
+        let gpio = gpio.clone();
+        let decoder = decoder.with_levels(dt.is_high(), clk.is_high());
+        std::thread::spawn(move || decode_edges(&gpio, &dt, &clk, decoder, &direction, &tx));
+
+        Ok(Self { _sw: sw, events })
+    }
+}
+
+fn is_rising(event: &Event) -> bool {
+    event.trigger == Trigger::RisingEdge
+}
+
+/// Waits for the edges of the encoder signals and sends the steps, until the
+/// encoder is dropped.
+fn decode_edges(
+    gpio: &Gpio,
+    dt: &InputPin,
+    clk: &InputPin,
+    mut decoder: Decoder,
+    direction: &EncoderDirection,
+    tx: &Sender<InputEvent>,
+) {
+    loop {
+        if let Err(e) = gpio.poll_interrupts(&[dt, clk], false, Some(EDGE_TIMEOUT)) {
+            error!("failed to wait for rotary encoder interrupts: {:?}", e);
+            return;
+        }
+        decoder.set_inverted(direction.is_inverted());
+        let event = match decoder.update(dt.is_high(), clk.is_high()) {
+            Some(Direction::Clockwise) => InputEvent::Up,
+            Some(Direction::CounterClockwise) => InputEvent::Down,
+            None => continue,
+        };
+        debug!("rotary encoder {:?}", event);
+        if tx.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+impl MenuInput for InterruptEncoder {
+    fn poll(&mut self) -> Option<InputEvent> {
+        // the merged input sleeps while no input has an event
+        self.events.try_recv().ok()
+    }
+}
@@ -0,0 +1,341 @@
+use std::time::{Duration, Instant};
+
+use embedded_hal::spi::{Operation, SpiDevice};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+
+use super::{InputEvent, MenuInput};
+
+/// Largest value of the 10 bit conversions of the MCP3008
+const ADC_MAX: u16 = 1023;
+/// Magnitude of a full deflection in [`InputEvent::Jog`]
+pub const JOG_MAX: i16 = 1000;
+
+/// MCP3008 8 channel 10 bit ADC.
+pub struct Mcp3008<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> Mcp3008<SPI>
+where
+    SPI: SpiDevice,
+{
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Reads the single ended input `channel` (0 to 7).
+    pub fn read(&mut self, channel: u8) -> Result<u16, SPI::Error> {
+        // start bit, single ended mode and channel, then clock out the result
+        let mut frame = [0x01, 0x80 | (channel & 0x07) << 4, 0x00];
+        self.spi
+            .transaction(&mut [Operation::TransferInPlace(&mut frame)])?;
+
+        Ok(u16::from(frame[1] & 0x03) << 8 | u16::from(frame[2]))
+    }
+}
+
+/// Raw ADC values of one axis of the stick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "CalibrationInFile")]
+pub struct AxisCalibration {
+    pub min: u16,
+    /// Value at rest, measured at startup
+    pub center: u16,
+    pub max: u16,
+    /// Reverses the axis, for sticks mounted the other way around
+    pub invert: bool,
+}
+
+impl Default for AxisCalibration {
+    fn default() -> Self {
+        Self {
+            min: 0,
+            center: ADC_MAX / 2,
+            max: ADC_MAX,
+            invert: false,
+        }
+    }
+}
+
+/// [`AxisCalibration`] as written in the config file, the ends may be
+/// swapped and the center outside of them
+#[derive(Deserialize)]
+#[serde(default)]
+struct CalibrationInFile {
+    min: u16,
+    center: u16,
+    max: u16,
+    invert: bool,
+}
+
+impl Default for CalibrationInFile {
+    fn default() -> Self {
+        let AxisCalibration {
+            min,
+            center,
+            max,
+            invert,
+        } = AxisCalibration::default();
+        Self {
+            min,
+            center,
+            max,
+            invert,
+        }
+    }
+}
+
+impl From<CalibrationInFile> for AxisCalibration {
+    fn from(calibration: CalibrationInFile) -> Self {
+        let min = calibration.min.min(calibration.max);
+        let max = calibration.min.max(calibration.max);
+        Self {
+            min,
+            center: calibration.center.clamp(min, max),
+            max,
+            invert: calibration.invert,
+        }
+    }
+}
+
+impl AxisCalibration {
+    /// Maps a raw value to the deflection from -1.0 to 1.0. Deflections
+    /// within the `deadzone` around the center are 0, the range outside of it
+    /// is stretched so the deflection still starts at 0.
+    pub fn deflection(&self, raw: u16, deadzone: f32) -> f32 {
+        let raw = raw.clamp(self.min, self.max);
+        let deflection = if raw >= self.center {
+            f32::from(raw - self.center) / f32::from((self.max - self.center).max(1))
+        } else {
+            -f32::from(self.center - raw) / f32::from((self.center - self.min).max(1))
+        };
+
+        let magnitude = deflection.abs();
+        if magnitude <= deadzone {
+            return 0.0;
+        }
+        let deflection = deflection.signum() * (magnitude - deadzone) / (1.0 - deadzone);
+        if self.invert { -deflection } else { deflection }
+    }
+}
+
+/// Settings of the analog joystick, part of the
+/// [`InputConfig`](super::InputConfig).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JoystickConfig {
+    pub enabled: bool,
+    /// ADC channel of the X axis
+    pub x_channel: u8,
+    /// ADC channel of the Y axis
+    pub y_channel: u8,
+    pub x: AxisCalibration,
+    pub y: AxisCalibration,
+    /// Fraction of the deflection around the center which is ignored
+    pub deadzone: f32,
+    /// Milliseconds between two jog events while the stick is deflected
+    pub jog_interval_ms: u64,
+    /// Distance the stage moves per jog event at full deflection
+    pub max_step: i64,
+}
+
+impl Default for JoystickConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            x_channel: 0,
+            y_channel: 1,
+            x: AxisCalibration::default(),
+            y: AxisCalibration::default(),
+            deadzone: 0.1,
+            jog_interval_ms: 100,
+            max_step: 500,
+        }
+    }
+}
+
+/// Analog two axis joystick read from a [`Mcp3008`].
+///
+/// While the stick is deflected, it emits an [`InputEvent::Jog`] with the
+/// deflection of both axes every `jog_interval_ms`.
+pub struct Joystick<SPI> {
+    adc: Mcp3008<SPI>,
+    config: JoystickConfig,
+    last_jog: Option<Instant>,
+}
+
+impl<SPI> Joystick<SPI>
+where
+    SPI: SpiDevice,
+{
+    /// Creates the joystick and takes the current position as center, so the
+    /// stick must not be touched at startup.
+    pub fn new(adc: Mcp3008<SPI>, config: &JoystickConfig) -> Self {
+        let mut joystick = Self {
+            adc,
+            config: config.clone(),
+            last_jog: None,
+        };
+        joystick.calibrate_center();
+        joystick
+    }
+
+    /// Takes the current position of the stick as rest position.
+    pub fn calibrate_center(&mut self) {
+        match self.read() {
+            Ok((x, y)) => {
+                info!("joystick center at {}, {}", x, y);
+                self.config.x.center = x.clamp(self.config.x.min, self.config.x.max);
+                self.config.y.center = y.clamp(self.config.y.min, self.config.y.max);
+            }
+            Err(e) => error!("failed to read joystick: {:?}", e),
+        }
+    }
+
+    fn read(&mut self) -> Result<(u16, u16), SPI::Error> {
+        Ok((
+            self.adc.read(self.config.x_channel)?,
+            self.adc.read(self.config.y_channel)?,
+        ))
+    }
+
+    /// Reads the stick at `now` and returns the jog event if one is due.
+    pub fn sample(&mut self, now: Instant) -> Option<InputEvent> {
+        let interval = Duration::from_millis(self.config.jog_interval_ms);
+        if self
+            .last_jog
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return None;
+        }
+
+        let (x, y) = self.read().ok()?;
+        let deadzone = self.config.deadzone;
+        let scale = |deflection: f32| (deflection * f32::from(JOG_MAX)).round() as i16;
+        let (x, y) = (
+            scale(self.config.x.deflection(x, deadzone)),
+            scale(self.config.y.deflection(y, deadzone)),
+        );
+        if x == 0 && y == 0 {
+            self.last_jog = None;
+            return None;
+        }
+
+        debug!("joystick jog {}, {}", x, y);
+        self.last_jog = Some(now);
+        Some(InputEvent::Jog { x, y })
+    }
+}
+
+impl<SPI> MenuInput for Joystick<SPI>
+where
+    SPI: SpiDevice,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        self.sample(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+
+    use super::*;
+
+    /// Expected transaction reading `value` from `channel`
+    fn conversion(channel: u8, value: u16) -> [Transaction<u8>; 3] {
+        [
+            Transaction::transaction_start(),
+            Transaction::transfer_in_place(
+                vec![0x01, 0x80 | channel << 4, 0x00],
+                vec![0x00, (value >> 8) as u8, value as u8],
+            ),
+            Transaction::transaction_end(),
+        ]
+    }
+
+    #[test]
+    fn test_mcp3008_read() {
+        let mut spi = Mock::new(&conversion(5, 0x2a5));
+        let mut adc = Mcp3008::new(spi.clone());
+
+        assert_eq!(adc.read(5).unwrap(), 0x2a5);
+        spi.done();
+    }
+
+    #[test]
+    fn test_deflection_with_deadzone() {
+        let axis = AxisCalibration {
+            min: 100,
+            center: 500,
+            max: 900,
+            invert: false,
+        };
+
+        assert_eq!(axis.deflection(520, 0.1), 0.0);
+        assert_eq!(axis.deflection(900, 0.1), 1.0);
+        assert_eq!(axis.deflection(0, 0.1), -1.0);
+        assert_eq!(axis.deflection(300, 0.0), -0.5);
+        assert!((axis.deflection(280, 0.1) + 0.5).abs() < 1e-6);
+        assert_eq!(
+            AxisCalibration {
+                invert: true,
+                ..axis
+            }
+            .deflection(700, 0.0),
+            -0.5
+        );
+    }
+
+    #[test]
+    fn test_swapped_calibration_is_sorted() {
+        let axis: AxisCalibration =
+            serde_json::from_str(r#"{ "min": 1000, "center": 1020, "max": 20 }"#).unwrap();
+        assert_eq!((axis.min, axis.center, axis.max), (20, 1000, 1000));
+        assert_eq!(axis.deflection(1023, 0.0), 0.0);
+        assert_eq!(axis.deflection(0, 0.0), -1.0);
+
+        let axis: AxisCalibration = serde_json::from_str(r#"{ "invert": true }"#).unwrap();
+        assert_eq!(
+            axis,
+            AxisCalibration {
+                invert: true,
+                ..AxisCalibration::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_jog_events_while_deflected() {
+        let readings = [
+            // calibration
+            (510, 500),
+            (1023, 500),
+            (510, 0),
+            (510, 500),
+        ];
+        let transactions: Vec<_> = readings
+            .iter()
+            .flat_map(|(x, y)| conversion(0, *x).into_iter().chain(conversion(1, *y)))
+            .collect();
+        let mut spi = Mock::new(&transactions);
+
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut joystick = Joystick::new(Mcp3008::new(spi.clone()), &JoystickConfig::default());
+
+        assert_eq!(
+            joystick.sample(start),
+            Some(InputEvent::Jog { x: 1000, y: 0 })
+        );
+        // no reading until the next interval
+        assert_eq!(joystick.sample(start + ms(50)), None);
+        assert_eq!(
+            joystick.sample(start + ms(100)),
+            Some(InputEvent::Jog { x: 0, y: -1000 })
+        );
+        assert_eq!(joystick.sample(start + ms(200)), None);
+        spi.done();
+    }
+}
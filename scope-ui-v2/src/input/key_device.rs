@@ -0,0 +1,294 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use evdev::{AttributeSet, Device, EventSummary, KeyCode};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{InputEvent, MenuInput, button::Action};
+
+/// Key values of the kernel input events
+const KEY_PRESSED: i32 = 1;
+const KEY_REPEATED: i32 = 2;
+
+/// What a key of an input device does, named like the encoder events it
+/// emulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCommand {
+    Up,
+    Down,
+    Select,
+    LongPress,
+    DoubleClick,
+    FineUp,
+    FineDown,
+    /// Runs the action, e.g. `{ "action": "capture" }`
+    Action(Action),
+}
+
+impl KeyCommand {
+    fn event(self) -> InputEvent {
+        match self {
+            Self::Up => InputEvent::Up,
+            Self::Down => InputEvent::Down,
+            Self::Select => InputEvent::Select,
+            Self::LongPress => InputEvent::LongPress,
+            Self::DoubleClick => InputEvent::DoubleClick,
+            Self::FineUp => InputEvent::FineUp,
+            Self::FineDown => InputEvent::FineDown,
+            Self::Action(action) => InputEvent::Action(action),
+        }
+    }
+
+    /// Whether holding the key down repeats the command
+    fn repeats(self) -> bool {
+        matches!(self, Self::Up | Self::Down | Self::FineUp | Self::FineDown)
+    }
+}
+
+/// Settings of the Linux input devices, part of the
+/// [`InputConfig`](super::InputConfig).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyDeviceConfig {
+    pub enabled: bool,
+    /// Event device to read, e.g. `/dev/input/event3`. Without a device all
+    /// devices with at least one mapped key are used.
+    pub device: Option<PathBuf>,
+    /// Takes the devices exclusively, so key presses do not reach the console
+    pub grab: bool,
+    /// Commands by kernel key name, e.g. `"KEY_KP8": "up"`
+    pub keys: BTreeMap<String, KeyCommand>,
+}
+
+impl Default for KeyDeviceConfig {
+    fn default() -> Self {
+        let keys = [
+            ("KEY_UP", KeyCommand::Up),
+            ("KEY_DOWN", KeyCommand::Down),
+            ("KEY_ENTER", KeyCommand::Select),
+            ("KEY_ESC", KeyCommand::LongPress),
+            ("KEY_KP8", KeyCommand::Up),
+            ("KEY_KP2", KeyCommand::Down),
+            ("KEY_KP9", KeyCommand::FineUp),
+            ("KEY_KP3", KeyCommand::FineDown),
+            ("KEY_KP5", KeyCommand::Select),
+            ("KEY_KPENTER", KeyCommand::Select),
+            ("KEY_KP0", KeyCommand::LongPress),
+            ("KEY_KPDOT", KeyCommand::DoubleClick),
+        ];
+
+        Self {
+            enabled: false,
+            device: None,
+            grab: false,
+            keys: keys
+                .into_iter()
+                .map(|(key, command)| (key.to_string(), command))
+                .collect(),
+        }
+    }
+}
+
+/// Key codes mapped to their commands.
+#[derive(Debug, Clone, Default)]
+pub struct KeyMap {
+    commands: HashMap<KeyCode, KeyCommand>,
+}
+
+impl KeyMap {
+    /// Parses the key names of the config, unknown names are skipped.
+    pub fn new(keys: &BTreeMap<String, KeyCommand>) -> Self {
+        let commands = keys
+            .iter()
+            .filter_map(|(name, command)| match KeyCode::from_str(name) {
+                Ok(code) => Some((code, *command)),
+                Err(_) => {
+                    warn!("unknown key {} in key map", name);
+                    None
+                }
+            })
+            .collect();
+
+        Self { commands }
+    }
+
+    /// Returns the event for a key event with the kernel `value`, 0 for
+    /// released, 1 for pressed and 2 for repeated.
+    pub fn event(&self, code: KeyCode, value: i32) -> Option<InputEvent> {
+        let command = self.commands.get(&code)?;
+        match value {
+            KEY_PRESSED => Some(command.event()),
+            KEY_REPEATED if command.repeats() => Some(command.event()),
+            _ => None,
+        }
+    }
+
+    fn keys(&self) -> AttributeSet<KeyCode> {
+        self.commands.keys().copied().collect()
+    }
+}
+
+/// [`MenuInput`] reading the keys of a Linux input device, like USB keypads,
+/// gamepads or foot pedals.
+pub struct KeyDevice {
+    device: Device,
+    path: PathBuf,
+    key_map: KeyMap,
+    events: VecDeque<InputEvent>,
+    disconnected: bool,
+}
+
+impl KeyDevice {
+    pub fn open(path: impl AsRef<Path>, key_map: KeyMap, grab: bool) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut device = Device::open(path)?;
+        device.set_nonblocking(true)?;
+        if grab {
+            device.grab()?;
+        }
+        info!(
+            "reading keys from {} ({})",
+            path.display(),
+            device.name().unwrap_or("unnamed")
+        );
+
+        Ok(Self {
+            device,
+            path: path.to_path_buf(),
+            key_map,
+            events: VecDeque::new(),
+            disconnected: false,
+        })
+    }
+
+    /// Opens the configured device, or all devices which have one of the
+    /// mapped keys.
+    pub fn open_configured(config: &KeyDeviceConfig) -> Vec<Self> {
+        let key_map = KeyMap::new(&config.keys);
+        let paths = match &config.device {
+            Some(path) => vec![path.clone()],
+            None => {
+                let keys = key_map.keys();
+                evdev::enumerate()
+                    .filter(|(_, device)| {
+                        device
+                            .supported_keys()
+                            .is_some_and(|supported| supported.iter().any(|key| keys.contains(key)))
+                    })
+                    .map(|(path, _)| path)
+                    .collect()
+            }
+        };
+
+        paths
+            .into_iter()
+            .filter_map(
+                |path| match Self::open(&path, key_map.clone(), config.grab) {
+                    Ok(device) => Some(device),
+                    Err(e) => {
+                        error!("failed to open input device {}: {}", path.display(), e);
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    fn fetch(&mut self) -> io::Result<()> {
+        for event in self.device.fetch_events()? {
+            if let EventSummary::Key(_, code, value) = event.destructure()
+                && let Some(event) = self.key_map.event(code, value)
+            {
+                debug!("key {:?} {:?}", code, event);
+                self.events.push_back(event);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MenuInput for KeyDevice {
+    fn poll(&mut self) -> Option<InputEvent> {
+        if self.events.is_empty() && !self.disconnected {
+            match self.fetch() {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => {
+                    error!("stop reading {}: {}", self.path.display(), e);
+                    self.disconnected = true;
+                }
+            }
+        }
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use evdev::{KeyEvent, uinput::VirtualDevice};
+
+    use super::*;
+
+    #[test]
+    fn test_key_map() {
+        let keys = serde_json::from_str(
+            r#"{
+                "KEY_KP8": "up",
+                "KEY_F13": { "action": "capture" },
+                "KEY_NOT_A_KEY": "down"
+            }"#,
+        )
+        .unwrap();
+        let key_map = KeyMap::new(&keys);
+
+        assert_eq!(key_map.event(KeyCode::KEY_KP8, 1), Some(InputEvent::Up));
+        assert_eq!(key_map.event(KeyCode::KEY_KP8, 2), Some(InputEvent::Up));
+        assert_eq!(key_map.event(KeyCode::KEY_KP8, 0), None);
+        assert_eq!(
+            key_map.event(KeyCode::KEY_F13, 1),
+            Some(InputEvent::Action(Action::Capture))
+        );
+        // actions do not repeat while the key is held
+        assert_eq!(key_map.event(KeyCode::KEY_F13, 2), None);
+        assert_eq!(key_map.event(KeyCode::KEY_KP2, 1), None);
+    }
+
+    /// Needs write access to `/dev/uinput`, run with `--ignored`.
+    #[test]
+    #[ignore]
+    fn test_virtual_keypad() {
+        let config = KeyDeviceConfig::default();
+        let keys: AttributeSet<KeyCode> =
+            [KeyCode::KEY_KP8, KeyCode::KEY_KP5].into_iter().collect();
+        let mut keypad = VirtualDevice::builder()
+            .unwrap()
+            .name("scope-ui test keypad")
+            .with_keys(&keys)
+            .unwrap()
+            .build()
+            .unwrap();
+        let path = keypad
+            .enumerate_dev_nodes_blocking()
+            .unwrap()
+            .find_map(Result::ok)
+            .unwrap();
+        let mut input = KeyDevice::open(path, KeyMap::new(&config.keys), false).unwrap();
+
+        let press = |key| [KeyEvent::new(key, 1).into(), KeyEvent::new(key, 0).into()];
+        keypad.emit(&press(KeyCode::KEY_KP8)).unwrap();
+        keypad.emit(&press(KeyCode::KEY_KP5)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(input.poll(), Some(InputEvent::Up));
+        assert_eq!(input.poll(), Some(InputEvent::Select));
+        assert_eq!(input.poll(), None);
+    }
+}
@@ -1,13 +1,134 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use quadrature::StepMode;
+use serde::{Deserialize, Serialize};
+
+use self::{
+    button::{Action, Binding, ButtonConfig},
+    gesture::GestureConfig,
+    joystick::JoystickConfig,
+    key_device::KeyDeviceConfig,
+};
+
+pub mod button;
+pub mod gesture;
+pub mod interrupt_encoder;
+pub mod joystick;
+pub mod key_device;
+pub mod record;
 pub mod rotary_encoder;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum InputEvent {
     Up,
     Down,
     Select,
     Quit,
+    /// The button was pressed, turned into gestures by the
+    /// [`GestureRecognizer`](gesture::GestureRecognizer)
+    ButtonDown,
+    /// The button was released
+    ButtonUp,
+    /// The button was held down
+    LongPress,
+    DoubleClick,
+    /// Turned up while the button is held down
+    FineUp,
+    /// Turned down while the button is held down
+    FineDown,
+    /// The joystick is deflected, both axes range from
+    /// [`-JOG_MAX`](joystick::JOG_MAX) to [`JOG_MAX`](joystick::JOG_MAX)
+    Jog {
+        x: i16,
+        y: i16,
+    },
+    /// A button or gesture bound to an action was triggered
+    Action(Action),
+}
+
+/// Input settings, part of the [`AppConfig`](crate::config::AppConfig).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Reverses the turning direction of the rotary encoder, for encoders
+    /// mounted the other way around. Other inputs keep their direction.
+    pub invert_encoder: bool,
+    /// Signal transitions per detent of the rotary encoder
+    pub step_mode: StepMode,
+    pub gestures: GestureConfig,
+    pub joystick: JoystickConfig,
+    /// Additional push buttons
+    pub buttons: Vec<ButtonConfig>,
+    /// Actions triggered by the buttons and gestures
+    pub bindings: Vec<Binding>,
+    /// USB keypads and other Linux input devices
+    pub keys: KeyDeviceConfig,
+}
+
+/// Turning direction of the rotary encoder, shared between the encoder and
+/// the UI, so inverting it on the settings screen applies at once.
+#[derive(Debug, Clone, Default)]
+pub struct EncoderDirection(Arc<AtomicBool>);
+
+impl EncoderDirection {
+    pub fn new(inverted: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(inverted)))
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set_inverted(&self, inverted: bool) {
+        self.0.store(inverted, Ordering::Relaxed);
+    }
 }
 
 pub trait MenuInput {
     fn poll(&mut self) -> Option<InputEvent>;
 }
+
+impl<I> MenuInput for Box<I>
+where
+    I: MenuInput + ?Sized,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        (**self).poll()
+    }
+}
+
+/// Polls several inputs in turn, so they can be used as one [`MenuInput`].
+#[derive(Default)]
+pub struct MergedInput {
+    inputs: Vec<Box<dyn MenuInput + Send>>,
+    next: usize,
+}
+
+impl MergedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, input: impl MenuInput + Send + 'static) {
+        self.inputs.push(Box::new(input));
+    }
+}
+
+impl MenuInput for MergedInput {
+    fn poll(&mut self) -> Option<InputEvent> {
+        // continue after the input polled last, so a busy input can not
+        // starve the others
+        for _ in 0..self.inputs.len() {
+            let idx = self.next % self.inputs.len();
+            self.next = idx + 1;
+            if let Some(event) = self.inputs[idx].poll() {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use super::{InputEvent, MenuInput};
+
+/// Line of a recording, the time is counted from the first poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub time_us: u64,
+    pub event: InputEvent,
+}
+
+/// [`MenuInput`] writing every event of another input to a file, one JSON
+/// object per line.
+pub struct Recorder<I> {
+    inner: I,
+    writer: BufWriter<File>,
+    start: Option<Instant>,
+}
+
+impl<I> Recorder<I> {
+    pub fn create(inner: I, path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create recording {}", path.display()))?;
+        info!("recording input to {}", path.display());
+
+        Ok(Self {
+            inner,
+            writer: BufWriter::new(file),
+            start: None,
+        })
+    }
+
+    fn record(&mut self, event: InputEvent, now: Instant) -> anyhow::Result<()> {
+        let start = *self.start.get_or_insert(now);
+        let line = RecordedEvent {
+            time_us: now.duration_since(start).as_micros() as u64,
+            event,
+        };
+        serde_json::to_writer(&mut self.writer, &line)?;
+        self.writer.write_all(b"\n")?;
+        // keep the recording complete if the UI crashes or freezes
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<I> MenuInput for Recorder<I>
+where
+    I: MenuInput,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        let now = Instant::now();
+        self.start.get_or_insert(now);
+
+        let event = self.inner.poll()?;
+        if let Err(e) = self.record(event, now) {
+            error!("failed to record input: {:?}", e);
+        }
+        Some(event)
+    }
+}
+
+/// [`MenuInput`] feeding back a recording of a [`Recorder`].
+pub struct Replay {
+    events: Vec<RecordedEvent>,
+    next: usize,
+    /// Replay speed, `2.0` replays twice as fast as recorded
+    speed: f64,
+    start: Option<Instant>,
+}
+
+impl Replay {
+    pub fn new(events: Vec<RecordedEvent>, speed: f64) -> Self {
+        Self {
+            events,
+            next: 0,
+            speed,
+            start: None,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>, speed: f64) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recording {}", path.display()))?;
+        let events = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(idx, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!("Failed to parse line {} of {}", idx + 1, path.display())
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        info!("replaying {} at {}x speed", path.display(), speed);
+
+        Ok(Self::new(events, speed))
+    }
+
+    /// Returns the next event if it is due `elapsed` after the start of the
+    /// replay.
+    pub fn next_due(&mut self, elapsed: Duration) -> Option<InputEvent> {
+        let recorded = self.events.get(self.next)?;
+        let due = Duration::from_micros(recorded.time_us).div_f64(self.speed);
+        if elapsed < due {
+            return None;
+        }
+
+        self.next += 1;
+        if self.next == self.events.len() {
+            info!("replay finished");
+        }
+        Some(recorded.event)
+    }
+}
+
+impl MenuInput for Replay {
+    fn poll(&mut self) -> Option<InputEvent> {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.next_due(start.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::button::Action;
+
+    /// Input returning the events from the back to the front
+    struct Script(Vec<InputEvent>);
+
+    impl MenuInput for Script {
+        fn poll(&mut self) -> Option<InputEvent> {
+            self.0.pop()
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("scope-ui-record-{}.jsonl", std::process::id()));
+        let events = [
+            InputEvent::Up,
+            InputEvent::Jog { x: -200, y: 1000 },
+            InputEvent::Action(Action::Capture),
+        ];
+
+        let input = Script(events.iter().rev().copied().collect());
+        let mut recorder = Recorder::create(input, &path).unwrap();
+        let recorded: Vec<_> = std::iter::from_fn(|| recorder.poll()).collect();
+        assert_eq!(recorded, events);
+        drop(recorder);
+
+        let mut replay = Replay::open(&path, 1.0).unwrap();
+        let replayed: Vec<_> =
+            std::iter::from_fn(|| replay.next_due(Duration::from_secs(60))).collect();
+        assert_eq!(replayed, events);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_accelerated_replay() {
+        let events = vec![
+            RecordedEvent {
+                time_us: 0,
+                event: InputEvent::Select,
+            },
+            RecordedEvent {
+                time_us: 1_000_000,
+                event: InputEvent::Down,
+            },
+        ];
+        let mut replay = Replay::new(events, 4.0);
+
+        assert_eq!(replay.next_due(Duration::ZERO), Some(InputEvent::Select));
+        assert_eq!(replay.next_due(Duration::from_millis(200)), None);
+        assert_eq!(
+            replay.next_due(Duration::from_millis(250)),
+            Some(InputEvent::Down)
+        );
+        assert_eq!(replay.next_due(Duration::from_secs(10)), None);
+    }
+}
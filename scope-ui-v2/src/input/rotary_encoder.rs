@@ -1,16 +1,20 @@
-use std::time::Duration;
-
 use embedded_hal::digital::InputPin;
 use log::debug;
 
-use super::{InputEvent, MenuInput};
+use quadrature::{Decoder, Direction};
+
+use super::{EncoderDirection, InputEvent, MenuInput};
 
+/// Rotary encoder reading its pins on every poll, used where the pins do not
+/// support interrupts. See
+/// [`InterruptEncoder`](super::interrupt_encoder::InterruptEncoder).
 pub struct RotaryEncoder<DT, CLK, SW> {
     dt: DT,
     clk: CLK,
     sw: SW,
     btn_state: u16,
-    rotary_state: [u16; 2],
+    decoder: Decoder,
+    direction: EncoderDirection,
 }
 
 impl<DT, CLK, SW> RotaryEncoder<DT, CLK, SW>
@@ -19,13 +23,14 @@ where
     CLK: InputPin,
     SW: InputPin,
 {
-    pub fn new(dt: DT, clk: CLK, sw: SW) -> Self {
+    pub fn new(dt: DT, clk: CLK, sw: SW, decoder: Decoder, direction: EncoderDirection) -> Self {
         Self {
             dt,
             clk,
             sw,
             btn_state: 0,
-            rotary_state: [0u16; 2],
+            decoder,
+            direction,
         }
     }
 }
@@ -37,46 +42,132 @@ where
     SW: InputPin,
 {
     fn poll(&mut self) -> Option<InputEvent> {
-        const ROT_ENC_TABLE: [u8; 16] = [0, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 1, 1, 0];
-
         let dt_value = self.dt.is_high().unwrap_or_default();
         let clk_value = self.clk.is_high().unwrap_or_default();
         let sw_value = self.sw.is_low().unwrap_or_default();
 
-        self.rotary_state[0] <<= 2;
-        if dt_value {
-            self.rotary_state[0] |= 0x02
-        }
-        if clk_value {
-            self.rotary_state[0] |= 0x01
-        }
-        self.rotary_state[0] &= 0x0f;
-
+        // a button state is reported once it is stable for eight polls
         self.btn_state = (self.btn_state << 1) | sw_value as u16 | 0xfe00;
+        if self.btn_state == 0xfeff {
+            debug!("rotary encoder button down");
+            return Some(InputEvent::ButtonDown);
+        }
         if self.btn_state == 0xff00 {
-            debug!("rotary encoder button click");
-            std::thread::sleep(Duration::from_millis(200));
-            return Some(InputEvent::Select);
+            debug!("rotary encoder button up");
+            return Some(InputEvent::ButtonUp);
         }
 
-        if ROT_ENC_TABLE[self.rotary_state[0] as usize] != 0 {
-            self.rotary_state[1] <<= 4;
-            self.rotary_state[1] |= self.rotary_state[0];
-
-            println!("{:02X?}", self.rotary_state[1]);
-            println!("{:02X?}", self.rotary_state[1] & 0xff);
-
-            if (self.rotary_state[1] & 0xff) == 0x2b {
+        self.decoder.set_inverted(self.direction.is_inverted());
+        match self.decoder.update(dt_value, clk_value) {
+            Some(Direction::Clockwise) => {
                 debug!("rotary encoder up");
-                return Some(InputEvent::Up);
+                Some(InputEvent::Up)
             }
-
-            if (self.rotary_state[1] & 0xff) == 0x17 {
+            Some(Direction::CounterClockwise) => {
                 debug!("rotary encoder down");
-                return Some(InputEvent::Down);
+                Some(InputEvent::Down)
             }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quadrature::{
+        StepMode,
+        mock::{MockClock, Script, ScriptedPin, Waveform},
+    };
+
+    use super::*;
+
+    const POLL_INTERVAL: Duration = Duration::from_micros(100);
+
+    /// Polls the encoder until all scripts ended and collects the events.
+    fn run(encoder: &mut impl MenuInput, clock: &MockClock, end: Duration) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        while clock.now() <= end + Duration::from_millis(10) {
+            events.extend(encoder.poll());
+            clock.advance(POLL_INTERVAL);
         }
+        events
+    }
+
+    #[test]
+    fn test_bouncy_turns() {
+        let clock = MockClock::new();
+        let (dt, clk) = Waveform::new(StepMode::Full)
+            .with_bounce(3, Duration::from_micros(50))
+            .scripts(&[2, -3]);
+        let end = dt.end().max(clk.end());
+        let mut encoder = RotaryEncoder::new(
+            ScriptedPin::new(dt, &clock),
+            ScriptedPin::new(clk, &clock),
+            ScriptedPin::new(Script::new(true), &clock),
+            Decoder::new(StepMode::Full),
+            EncoderDirection::default(),
+        );
+
+        use InputEvent::*;
+        assert_eq!(run(&mut encoder, &clock, end), [Up, Up, Down, Down, Down]);
+    }
+
+    #[test]
+    fn test_inverted_turns() {
+        let clock = MockClock::new();
+        let (dt, clk) = Waveform::new(StepMode::Full).scripts(&[1, -2, 1]);
+        let direction = EncoderDirection::new(true);
+        let mut encoder = RotaryEncoder::new(
+            ScriptedPin::new(dt, &clock),
+            ScriptedPin::new(clk, &clock),
+            ScriptedPin::new(Script::new(true), &clock),
+            Decoder::new(StepMode::Full),
+            direction.clone(),
+        );
+
+        let mut turn_until = |until| {
+            let mut events = Vec::new();
+            while clock.now() < until {
+                events.extend(encoder.poll());
+                clock.advance(POLL_INTERVAL);
+            }
+            events
+        };
+
+        use InputEvent::*;
+        // the waveform turns one detent per 4 ms
+        assert_eq!(turn_until(Duration::from_millis(4)), [Down]);
+        direction.set_inverted(false);
+        assert_eq!(turn_until(Duration::from_millis(20)), [Down, Down, Up]);
+    }
+
+    #[test]
+    fn test_bouncy_button_press() {
+        let ms = Duration::from_millis;
+        let clock = MockClock::new();
+        let (dt, clk) = Waveform::new(StepMode::Full).scripts(&[]);
+        // the button pulls the pin low
+        let sw = Script::new(true)
+            .then(ms(10), false)
+            .then(ms(10) + POLL_INTERVAL, true)
+            .then(ms(10) + 3 * POLL_INTERVAL, false)
+            .then(ms(50), true)
+            .then(ms(50) + 2 * POLL_INTERVAL, false)
+            .then(ms(50) + 4 * POLL_INTERVAL, true);
+        let end = sw.end();
+        let mut encoder = RotaryEncoder::new(
+            ScriptedPin::new(dt, &clock),
+            ScriptedPin::new(clk, &clock),
+            ScriptedPin::new(sw, &clock),
+            Decoder::new(StepMode::Full),
+            EncoderDirection::default(),
+        );
 
-        None
+        assert_eq!(
+            run(&mut encoder, &clock, end),
+            [InputEvent::ButtonDown, InputEvent::ButtonUp]
+        );
     }
 }
@@ -1,3 +1,15 @@
 pub mod client;
+pub mod config;
+pub mod console;
+pub mod diagnostics;
 pub mod display;
+pub mod history;
+pub mod i18n;
 pub mod input;
+pub mod limits;
+pub mod logging;
+pub mod power;
+pub mod request;
+pub mod theme;
+pub mod ui;
+pub mod units;
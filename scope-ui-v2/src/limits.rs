@@ -0,0 +1,245 @@
+//! Software travel limits of the stage, checked before every stage move.
+//!
+//! The slider has no limits here. Phoenix keeps track of its position and
+//! refuses moves beyond `boundary_sanga_start` and `boundary_sanga_end`,
+//! which are the only limits of the slider.
+
+use std::fmt;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::client::{OpenFlexurePosition, OpenflexureAxis};
+
+/// Range an axis may move in, in motor steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "LimitsInFile")]
+pub struct AxisLimits {
+    pub min: i64,
+    pub max: i64,
+}
+
+/// [`AxisLimits`] as written in the config file, possibly swapped
+#[derive(Deserialize)]
+struct LimitsInFile {
+    min: i64,
+    max: i64,
+}
+
+impl From<LimitsInFile> for AxisLimits {
+    fn from(limits: LimitsInFile) -> Self {
+        Self::between(limits.min, limits.max)
+    }
+}
+
+impl AxisLimits {
+    /// Limits between two positions, in any order.
+    pub fn between(a: i64, b: i64) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+}
+
+/// What happens to a move whose target is outside of the limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitMode {
+    /// The limits are not checked
+    Off,
+    /// The stage moves up to the limit
+    #[default]
+    Clamp,
+    /// The move is not sent
+    Reject,
+}
+
+impl LimitMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Clamp,
+            Self::Clamp => Self::Reject,
+            Self::Reject => Self::Off,
+        }
+    }
+}
+
+/// End of an axis the stage is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Min,
+    Max,
+}
+
+/// Limits of the axes, axes without limits move freely.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoftLimits {
+    pub mode: LimitMode,
+    pub x: Option<AxisLimits>,
+    pub y: Option<AxisLimits>,
+    pub z: Option<AxisLimits>,
+}
+
+/// A move was not sent, because its target is outside of the limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitError {
+    pub axis: OpenflexureAxis,
+    pub target: i64,
+    pub limits: AxisLimits,
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} target {} outside of limits {}..{}",
+            self.axis, self.target, self.limits.min, self.limits.max
+        )
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl SoftLimits {
+    /// Limits of `axis`, `None` if it has none or the limits are off.
+    pub fn axis(&self, axis: OpenflexureAxis) -> Option<AxisLimits> {
+        if self.mode == LimitMode::Off {
+            return None;
+        }
+        self.configured(axis)
+    }
+
+    /// Limits set for `axis`, also while the limits are off.
+    pub fn configured(&self, axis: OpenflexureAxis) -> Option<AxisLimits> {
+        match axis {
+            OpenflexureAxis::X => self.x,
+            OpenflexureAxis::Y => self.y,
+            OpenflexureAxis::Z => self.z,
+        }
+    }
+
+    pub fn set_axis(&mut self, axis: OpenflexureAxis, limits: Option<AxisLimits>) {
+        match axis {
+            OpenflexureAxis::X => self.x = limits,
+            OpenflexureAxis::Y => self.y = limits,
+            OpenflexureAxis::Z => self.z = limits,
+        }
+    }
+
+    /// Returns the end of `axis` the position `value` is at or beyond.
+    pub fn at_limit(&self, axis: OpenflexureAxis, value: i64) -> Option<Limit> {
+        let limits = self.axis(axis)?;
+        if value <= limits.min {
+            Some(Limit::Min)
+        } else if value >= limits.max {
+            Some(Limit::Max)
+        } else {
+            None
+        }
+    }
+
+    /// Checks the target of a move. Returns the target to move to, which is
+    /// clamped to the limits or rejected with a [`LimitError`] depending on
+    /// the mode.
+    pub fn check(&self, target: OpenFlexurePosition) -> Result<OpenFlexurePosition, LimitError> {
+        let mut checked = target;
+        for axis in OpenflexureAxis::ALL {
+            let Some(limits) = self.axis(axis) else {
+                continue;
+            };
+            let value = target.axis(axis);
+            if (limits.min..=limits.max).contains(&value) {
+                continue;
+            }
+
+            let error = LimitError {
+                axis,
+                target: value,
+                limits,
+            };
+            if self.mode == LimitMode::Reject {
+                return Err(error);
+            }
+            warn!("{}, clamping", error);
+            let clamped = value.clamp(limits.min, limits.max);
+            match axis {
+                OpenflexureAxis::X => checked.x = clamped,
+                OpenflexureAxis::Y => checked.y = clamped,
+                OpenflexureAxis::Z => checked.z = clamped,
+            }
+        }
+        Ok(checked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(mode: LimitMode) -> SoftLimits {
+        SoftLimits {
+            mode,
+            x: Some(AxisLimits::between(1000, -1000)),
+            y: None,
+            z: Some(AxisLimits { min: 0, max: 500 }),
+        }
+    }
+
+    fn position(x: i64, y: i64, z: i64) -> OpenFlexurePosition {
+        OpenFlexurePosition { x, y, z }
+    }
+
+    #[test]
+    fn test_clamp() {
+        let limits = limits(LimitMode::Clamp);
+        assert_eq!(
+            limits.check(position(2000, 99_999, -10)),
+            Ok(position(1000, 99_999, 0))
+        );
+        assert_eq!(limits.check(position(5, 5, 5)), Ok(position(5, 5, 5)));
+    }
+
+    #[test]
+    fn test_reject() {
+        let limits = limits(LimitMode::Reject);
+        assert_eq!(
+            limits.check(position(0, 0, 501)),
+            Err(LimitError {
+                axis: OpenflexureAxis::Z,
+                target: 501,
+                limits: AxisLimits { min: 0, max: 500 },
+            })
+        );
+        assert!(limits.check(position(-1000, 0, 500)).is_ok());
+    }
+
+    #[test]
+    fn test_off() {
+        let limits = limits(LimitMode::Off);
+        assert!(limits.check(position(2000, 0, 0)).is_ok());
+        assert_eq!(limits.at_limit(OpenflexureAxis::X, 2000), None);
+    }
+
+    #[test]
+    fn test_swapped_limits_are_sorted() {
+        let limits: AxisLimits = serde_json::from_str(r#"{ "min": 500, "max": -500 }"#).unwrap();
+        assert_eq!(
+            limits,
+            AxisLimits {
+                min: -500,
+                max: 500
+            }
+        );
+    }
+
+    #[test]
+    fn test_at_limit() {
+        let limits = limits(LimitMode::Clamp);
+        assert_eq!(limits.at_limit(OpenflexureAxis::X, -1000), Some(Limit::Min));
+        assert_eq!(limits.at_limit(OpenflexureAxis::X, 999), None);
+        assert_eq!(limits.at_limit(OpenflexureAxis::Z, 600), Some(Limit::Max));
+        assert_eq!(limits.at_limit(OpenflexureAxis::Y, 600), None);
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Log records kept for the log screen.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    inner: Arc<Mutex<LogLines>>,
+}
+
+#[derive(Default)]
+struct LogLines {
+    lines: VecDeque<String>,
+    capacity: usize,
+    /// Number of lines pushed since the start, used to find new lines
+    total: u64,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LogLines {
+                lines: VecDeque::with_capacity(capacity),
+                capacity,
+                total: 0,
+            })),
+        }
+    }
+
+    pub fn push(&self, line: String) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.lines.len() == inner.capacity {
+            inner.lines.pop_front();
+        }
+        inner.lines.push_back(line);
+        inner.total += 1;
+    }
+
+    /// Returns the number of lines pushed since the start.
+    pub fn total(&self) -> u64 {
+        self.inner.lock().unwrap().total
+    }
+
+    /// Returns the lines pushed after `seen` lines, as far as they are still
+    /// kept, together with the new total.
+    pub fn since(&self, seen: u64) -> (Vec<String>, u64) {
+        let inner = self.inner.lock().unwrap();
+        let new = (inner.total - seen.min(inner.total)).min(inner.lines.len() as u64) as usize;
+        let lines = inner.lines.iter().skip(inner.lines.len() - new).cloned();
+
+        (lines.collect(), inner.total)
+    }
+}
+
+/// Logger forwarding to `env_logger` while also keeping records up to `level`
+/// in a [`LogBuffer`], independent of `RUST_LOG`.
+pub struct UiLogger {
+    inner: env_logger::Logger,
+    buffer: LogBuffer,
+    level: LevelFilter,
+}
+
+impl UiLogger {
+    /// Installs the logger and returns the buffer it writes to.
+    pub fn init(level: LevelFilter, capacity: usize) -> LogBuffer {
+        let inner = env_logger::Builder::from_default_env().build();
+        let buffer = LogBuffer::new(capacity);
+        let max_level = inner.filter().max(level);
+
+        let logger = Self {
+            inner,
+            buffer: buffer.clone(),
+            level,
+        };
+        log::set_boxed_logger(Box::new(logger)).expect("logger is already initialized");
+        log::set_max_level(max_level);
+
+        buffer
+    }
+}
+
+impl Log for UiLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+
+        if record.level() <= self.level {
+            let marker = match record.level() {
+                Level::Error => 'E',
+                Level::Warn => 'W',
+                Level::Info => 'I',
+                Level::Debug => 'D',
+                Level::Trace => 'T',
+            };
+            self.buffer.push(format!("{} {}", marker, record.args()));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_returns_new_lines() {
+        let buffer = LogBuffer::new(3);
+        buffer.push("a".to_string());
+        buffer.push("b".to_string());
+
+        let (lines, seen) = buffer.since(0);
+        assert_eq!(lines, vec!["a", "b"]);
+        assert_eq!(seen, 2);
+
+        buffer.push("c".to_string());
+        buffer.push("d".to_string());
+        assert_eq!(buffer.since(seen), (vec!["c".into(), "d".into()], 4));
+    }
+
+    #[test]
+    fn test_buffer_drops_oldest_lines() {
+        let buffer = LogBuffer::new(2);
+        for line in ["a", "b", "c"] {
+            buffer.push(line.to_string());
+        }
+
+        assert_eq!(buffer.since(0), (vec!["b".into(), "c".into()], 3));
+    }
+}
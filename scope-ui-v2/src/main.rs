@@ -1,431 +1,404 @@
-use std::{fmt::Debug, sync::mpsc, time::Duration};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
+use anyhow::{Context, bail};
 use display_interface_spi::SPIInterface;
-use embedded_graphics::{
-    mono_font::{MonoTextStyleBuilder, ascii::FONT_10X20, iso_8859_3::FONT_9X18_BOLD},
-    pixelcolor::Rgb565,
-    prelude::*,
-    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
-    text::Text,
-};
-use embedded_layout::{
-    align::{Align, horizontal, vertical},
-    layout::linear::{FixedMargin, LinearLayout},
-    prelude::*,
-};
 use linux_embedded_hal::{
-    Delay, SpidevDevice,
+    Delay, I2cdev, SpidevDevice,
     spidev::{SpiModeFlags, Spidev, SpidevOptions},
 };
-use log::{debug, error};
+use log::{LevelFilter, debug, error, warn};
+use quadrature::Decoder;
 use rppal::gpio::Gpio;
 use scope_ui::{
-    client::{AppClient, AppConfig, OpenflexureAxis},
+    config::AppConfig,
     display::{
-        Flushable,
-        ili9341::{DisplaySize240x320, Ili9341, Orientation},
+        Panel, PanelModel, PanelOrientation,
+        ili9341::{DisplaySize240x320, Ili9341},
+        mono::MonoPanel,
+        simulated::SimulatedPanel,
+        ssd1306::{SSD1306_ADDRESS, Ssd1306},
+        st77xx::{DisplaySize128x160, DisplaySize240x240, Model, St77xx},
     },
-    input::{MenuInput, rotary_encoder::RotaryEncoder},
+    input::{
+        EncoderDirection, InputEvent, MenuInput, MergedInput,
+        button::{Button, apply_bindings, button_action},
+        gesture::GestureInput,
+        interrupt_encoder::InterruptEncoder,
+        joystick::{Joystick, JoystickConfig, Mcp3008},
+        key_device::KeyDevice,
+        record::{Recorder, Replay},
+        rotary_encoder::RotaryEncoder,
+    },
+    logging::{LogBuffer, UiLogger},
+    power::{Backlight, PowerManager, PowerState},
+    request::Request,
+    theme::Theme,
+    ui::{App, AppEvent},
 };
+use tokio::{sync::mpsc, time::MissedTickBehavior};
 
 const DC_PIN: u8 = 24;
 const RST_PIN: u8 = 25;
 const ROTARY_CLK: u8 = 17;
 const ROTARY_DT: u8 = 18;
 const ROTARY_SW: u8 = 27;
+const BACKLIGHT_PIN: u8 = 16;
+const I2C_DEVICE: &str = "/dev/i2c-1";
+/// SPI device of the joystick ADC, the display uses chip select 0
+const JOYSTICK_SPI_DEVICE: &str = "/dev/spidev0.1";
+
+const DEFAULT_CONFIG_PATH: &str = "scope-ui.json";
+const POWER_TICK: Duration = Duration::from_millis(500);
+/// Shortest time between two frames, events arriving in between are drawn
+/// together
+const FRAME_INTERVAL: Duration = Duration::from_millis(30);
+const SPLASH_DURATION: Duration = Duration::from_secs(3);
+/// Pause between two polls of the input without events, the polled encoder
+/// needs eight polls to debounce the button
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// Number of log records kept for the log view
+const LOG_CAPACITY: usize = 200;
+
+const USAGE: &str = "\
+Usage: scope-ui [OPTIONS]
+
+Options:
+  --record <FILE>    Write all input events to FILE
+  --replay <FILE>    Read the input events from a recording instead of the hardware
+  --speed <FACTOR>   Replay speed, 2 replays twice as fast as recorded [default: 1]
+  --simulate <FILE>  Draw on a simulated display, writing every frame to FILE as PPM image
+
+The config file is read from $SCOPE_UI_CONFIG, default scope-ui.json.";
+
+/// Command line options, used to reproduce bugs without the hardware.
+#[derive(Debug, PartialEq)]
+struct Args {
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    speed: f64,
+    simulate: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut parsed = Self {
+            record: None,
+            replay: None,
+            speed: 1.0,
+            simulate: None,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing value of {}", arg))
+            };
+            match arg.as_str() {
+                "--record" => parsed.record = Some(value()?.into()),
+                "--replay" => parsed.replay = Some(value()?.into()),
+                "--speed" => {
+                    parsed.speed = value()?.parse().context("Invalid replay speed")?;
+                    if parsed.speed <= 0.0 || !parsed.speed.is_finite() {
+                        bail!("Replay speed has to be positive");
+                    }
+                }
+                "--simulate" => parsed.simulate = Some(value()?.into()),
+                _ => bail!("Unknown argument {}", arg),
+            }
+        }
+        Ok(parsed)
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+    let log = UiLogger::init(LevelFilter::Info, LOG_CAPACITY);
+    let config_path = PathBuf::from(
+        std::env::var("SCOPE_UI_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string()),
+    );
+    let config = AppConfig::load_or_default(&config_path);
+    let direction = EncoderDirection::new(config.input.invert_encoder);
+
+    let input: Box<dyn MenuInput + Send> = match &args.replay {
+        Some(path) => Box::new(Replay::open(path, args.speed).expect("Failed to open recording")),
+        None => Box::new(create_input(&config, &direction)),
+    };
+    let input: Box<dyn MenuInput + Send> = match &args.record {
+        Some(path) => Box::new(Recorder::create(input, path).expect("Failed to create recording")),
+        None => input,
+    };
+
+    let orientation = config.orientation;
+    let theme = Theme::for_panel(config.panel);
+    if let Some(output) = &args.simulate {
+        let display =
+            SimulatedPanel::new(config.panel.resolution(), orientation).with_output(output);
+        return run(config, config_path, direction, log, display, input, ()).await;
+    }
+    if config.panel == PanelModel::Ssd1306 {
+        let i2c = I2cdev::new(I2C_DEVICE).expect("Failed to setup i2c device");
+        let mut display = Ssd1306::new(i2c, SSD1306_ADDRESS, false).unwrap();
+        display.set_orientation(orientation).unwrap();
+        let display = MonoPanel::new(display, theme);
+        return run(config, config_path, direction, log, display, input, ()).await;
+    }
+
     let gpio = Gpio::new().expect("Failed to setup gpio");
     let spidev = create_spi().expect("Failed to setup spi device");
     let spi = SpidevDevice(spidev);
     let dc_pin = gpio.get(DC_PIN).unwrap().into_output();
     let rst_pin = gpio.get(RST_PIN).unwrap().into_output();
-
-    let rotary_clk = gpio.get(ROTARY_CLK).expect("Invalid CLK pin").into_input();
-    let rotary_dt = gpio.get(ROTARY_DT).expect("Invalid DT pin").into_input();
-    let rotary_sw = gpio.get(ROTARY_SW).expect("Invalid SW pin").into_input();
-
-    let mut input = RotaryEncoder::new(rotary_clk, rotary_dt, rotary_sw);
-
-    let config = AppConfig {
-        openflexure_url: "http://localhost:5000".try_into().unwrap(),
-        phoenix_url: "http://localhost:4000".try_into().unwrap(),
-    };
-
+    let backlight = gpio
+        .get(BACKLIGHT_PIN)
+        .expect("Invalid backlight pin")
+        .into_output();
     let iface = SPIInterface::new(spi, dc_pin);
-    let display = Ili9341::new(
-        iface,
-        rst_pin,
-        &mut Delay,
-        Orientation::LandscapeFlipped,
-        DisplaySize240x320,
-    )
-    .unwrap();
 
-    let mut app = App::new(&config, display);
+    match config.panel {
+        PanelModel::Ili9341 => {
+            let display =
+                Ili9341::new(iface, rst_pin, &mut Delay, orientation, DisplaySize240x320).unwrap();
+            run(
+                config,
+                config_path,
+                direction,
+                log,
+                display,
+                input,
+                backlight,
+            )
+            .await
+        }
+        PanelModel::St7735 => {
+            let display = St77xx::new(
+                iface,
+                rst_pin,
+                &mut Delay,
+                Model::St7735,
+                orientation,
+                DisplaySize128x160,
+            )
+            .unwrap();
+            run(
+                config,
+                config_path,
+                direction,
+                log,
+                display,
+                input,
+                backlight,
+            )
+            .await
+        }
+        PanelModel::St7789 => {
+            let display = St77xx::new(
+                iface,
+                rst_pin,
+                &mut Delay,
+                Model::St7789,
+                orientation,
+                DisplaySize240x240,
+            )
+            .unwrap();
+            run(
+                config,
+                config_path,
+                direction,
+                log,
+                display,
+                input,
+                backlight,
+            )
+            .await
+        }
+        PanelModel::Ssd1306 => unreachable!("the I2C panel is set up before the SPI bus"),
+    }
+}
+
+async fn run<D, I, B>(
+    config: AppConfig,
+    config_path: PathBuf,
+    direction: EncoderDirection,
+    log: LogBuffer,
+    display: D,
+    mut input: I,
+    backlight: B,
+) where
+    D: Panel,
+    I: MenuInput + Send + 'static,
+    B: Backlight,
+{
+    let theme = Theme::for_panel(config.panel);
+    let (event_tx, mut events) = mpsc::unbounded_channel();
+    let mut power = PowerManager::new(backlight, config.power.clone(), Instant::now());
+    let mut app = App::new(config, config_path, theme, display, log, event_tx.clone());
 
     app.clear();
-    app.splash_screen(Rgb565::CSS_ORANGE);
-    app.setup().await;
-    std::thread::sleep(Duration::from_secs(3));
-    app.clear();
-    app.draw().unwrap();
+    app.splash_screen(theme.accent);
+    app.request(Request::RefreshPosition);
+    tokio::time::sleep(SPLASH_DURATION).await;
 
-    // run poll input in other thread
-    let (event_tx, event_rx) = mpsc::channel();
+    // polling the input blocks, so it runs in its own thread
     std::thread::spawn(move || {
         loop {
-            if let Some(event) = input.poll() {
-                event_tx.send(event).unwrap();
+            match input.poll() {
+                Some(event) => {
+                    if event_tx.send(AppEvent::Input(event)).is_err() {
+                        break;
+                    }
+                }
+                None => std::thread::sleep(INPUT_POLL_INTERVAL),
             }
         }
     });
 
+    let mut power_tick = tokio::time::interval(POWER_TICK);
+    let mut frame_tick = tokio::time::interval(FRAME_INTERVAL);
+    frame_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
     loop {
-        if let Ok(event) = event_rx.recv() {
-            debug!("receive event {:?}", event);
-            match event {
-                scope_ui::input::InputEvent::Up => app.increase().await,
-                scope_ui::input::InputEvent::Down => app.decrease().await,
-                scope_ui::input::InputEvent::Select => app.trigger_control_mode(),
-                scope_ui::input::InputEvent::Quit => {}
-            }
-            app.clear();
+        tokio::select! {
+            event = events.recv() => match event {
+                Some(AppEvent::Input(event)) => {
+                    handle_input(&mut app, &mut power, &direction, event);
+                }
+                Some(AppEvent::Completed { request, result }) => app.completed(request, result),
+                None => break,
+            },
+            _ = power_tick.tick() => {
+                if power.tick(Instant::now()) == Some(PowerState::Sleeping) {
+                    app.sleep();
+                }
+                app.tick(Instant::now());
+            },
+            _ = frame_tick.tick(), if app.is_dirty() && power.state() != PowerState::Sleeping => {
+                if let Err(e) = app.draw() {
+                    error!("{:?}", e);
+                }
+            },
         }
-
-        app.draw().unwrap();
-        // app.flush().unwrap();
     }
 }
 
-fn create_spi() -> Result<Spidev, std::io::Error> {
-    let mut spi = Spidev::open("/dev/spidev0.0")?;
-    let options = SpidevOptions::new()
-        .bits_per_word(8)
-        .max_speed_hz(0x07735940)
-        .mode(SpiModeFlags::SPI_MODE_0)
-        .build();
-    spi.configure(&options)?;
-
-    Ok(spi)
-}
-
-struct MenuSelection {
-    name: &'static str,
-    value: i64,
-}
-
-impl MenuSelection {
-    fn new(name: &'static str, value: i64) -> Self {
-        Self { name, value }
-    }
-}
-
-struct App<D>
-where
-    D: DrawTarget<Color = Rgb565, Error: Debug> + Flushable,
+fn handle_input<D, B>(
+    app: &mut App<D>,
+    power: &mut PowerManager<B>,
+    direction: &EncoderDirection,
+    event: InputEvent,
+) where
+    D: Panel,
+    B: Backlight,
 {
-    client: AppClient,
-    display: D,
-    selection_idx: u32,
-    selections: Box<[MenuSelection]>,
-    contol_mode: bool,
-}
-
-impl<D> Drop for App<D>
-where
-    D: DrawTarget<Color = Rgb565, Error: Debug> + Flushable,
-{
-    fn drop(&mut self) {
-        self.clear();
-        self.splash_screen(Rgb565::CSS_GRAY);
-    }
-}
-
-impl<D> App<D>
-where
-    D: DrawTarget<Color = Rgb565, Error: Debug> + Flushable,
-{
-    pub fn new(config: &AppConfig, display: D) -> Self {
-        let client = AppClient::new(config);
-        let selections = [
-            MenuSelection::new("X Axis", 0),
-            MenuSelection::new("Y Axis", 0),
-            MenuSelection::new("Z Axis", 0),
-            MenuSelection::new("Slider", 0),
-        ];
-        Self {
-            client,
-            display,
-            selections: Box::new(selections),
-            selection_idx: 0,
-            contol_mode: false,
+    debug!("receive event {:?}", event);
+    match power.activity(Instant::now()) {
+        PowerState::Active => {}
+        PowerState::Dimmed => return,
+        PowerState::Sleeping => {
+            // the event only wakes up the display
+            app.wake();
+            return;
         }
     }
 
-    pub async fn setup(&mut self) {
-        let flexure_values = self
-            .client
-            .get_openflexure_position()
-            .await
-            .unwrap_or_default();
+    app.handle(apply_bindings(&app.config().input.bindings, event));
 
-        self.selections[0].value = flexure_values.x;
-        self.selections[1].value = flexure_values.y;
-        self.selections[2].value = flexure_values.z;
+    // the brightness and the timeouts can be changed on the settings screen
+    if app.config().power != *power.config() {
+        power.set_config(app.config().power.clone());
     }
+    // so is the turning direction of the encoder
+    direction.set_inverted(app.config().input.invert_encoder);
 }
 
-impl<D> App<D>
-where
-    D: DrawTarget<Color = Rgb565, Error: Debug> + Flushable,
-{
-    pub fn draw(&mut self) -> anyhow::Result<()> {
-        let thick_stroke = PrimitiveStyle::with_stroke(Rgb565::WHITE, 3);
-        self.display
-            .bounding_box()
-            .into_styled(thick_stroke)
-            .draw(&mut self.display)
-            .unwrap();
-
-        self.draw_menu()
+/// Sets up the encoder and all other configured inputs.
+fn create_input(config: &AppConfig, direction: &EncoderDirection) -> MergedInput {
+    let gpio = Gpio::new().expect("Failed to setup gpio");
+    let decoder = Decoder::new(config.input.step_mode);
+    let encoder = create_encoder(&gpio, decoder, direction);
+    let mut input = MergedInput::new();
+    input.push(GestureInput::new(encoder, &config.input.gestures));
+    for button in &config.input.buttons {
+        let Some(action) = button_action(&config.input.bindings, &button.name) else {
+            continue;
+        };
+        let pin = gpio.get(button.pin).expect("Invalid button pin");
+        let pin = if button.active_low {
+            pin.into_input_pullup()
+        } else {
+            pin.into_input_pulldown()
+        };
+        input.push(Button::new(pin, button.active_low, action, Instant::now()));
     }
-
-    fn trigger_control_mode(&mut self) {
-        self.contol_mode = !self.contol_mode;
-        debug!("switch control mode to {}", self.contol_mode);
+    if config.input.keys.enabled {
+        for device in KeyDevice::open_configured(&config.input.keys) {
+            input.push(device);
+        }
     }
-
-    fn draw_menu(&mut self) -> anyhow::Result<()> {
-        let display_area = self.display.bounding_box();
-
-        let text_style = MonoTextStyleBuilder::new()
-            .font(&FONT_9X18_BOLD)
-            .text_color(Rgb565::WHITE)
-            .build();
-
-        let selector_style = MonoTextStyleBuilder::new()
-            .font(&FONT_9X18_BOLD)
-            .text_color(Rgb565::CSS_ORANGE)
-            .build();
-
-        let selector_style_invisible = MonoTextStyleBuilder::new()
-            .font(&FONT_9X18_BOLD)
-            .text_color(Rgb565::BLACK)
-            .build();
-
-        let control_style = MonoTextStyleBuilder::new()
-            .font(&FONT_9X18_BOLD)
-            .text_color(if self.contol_mode {
-                Rgb565::CSS_ORANGE
-            } else {
-                Rgb565::CSS_GRAY
-            })
-            .build();
-
-        let mut selector = Vec::with_capacity(3);
-        match dbg!(self.selection_idx) {
-            0 => {
-                selector.push(Text::new(">", Point::zero(), selector_style));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-            }
-            1 => {
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-            }
-            2 => {
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-            }
-            3 => {
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style));
-            }
-            _ => {
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-                selector.push(Text::new(">", Point::zero(), selector_style_invisible));
-            }
+    if config.input.joystick.enabled {
+        match create_joystick(&config.input.joystick) {
+            Ok(joystick) => input.push(joystick),
+            Err(e) => error!("failed to set up joystick: {}", e),
         }
-        let x_axis = LinearLayout::horizontal(Chain::new(selector[0]).append(Text::new(
-            self.selections[0].name,
-            Point::zero(),
-            text_style,
-        )))
-        .with_spacing(FixedMargin(5))
-        .arrange();
-
-        let y_axis = LinearLayout::horizontal(Chain::new(selector[1]).append(Text::new(
-            self.selections[1].name,
-            Point::zero(),
-            text_style,
-        )))
-        .with_spacing(FixedMargin(5))
-        .arrange();
-
-        let z_axis = LinearLayout::horizontal(Chain::new(selector[2]).append(Text::new(
-            self.selections[2].name,
-            Point::zero(),
-            text_style,
-        )))
-        .with_spacing(FixedMargin(5))
-        .arrange();
-
-        let slider = LinearLayout::horizontal(Chain::new(selector[3]).append(Text::new(
-            self.selections[3].name,
-            Point::zero(),
-            text_style,
-        )))
-        .with_spacing(FixedMargin(5))
-        .arrange();
-
-        let control_txt = format!("Control Mode: {}", self.contol_mode);
-        let control = Text::new(&control_txt, Point::zero(), control_style);
-
-        LinearLayout::vertical(
-            Chain::new(
-                LinearLayout::horizontal(Chain::new(x_axis).append(Text::new(
-                    format!("{}", self.selections[0].value).as_str(),
-                    Point::zero(),
-                    text_style,
-                )))
-                .with_spacing(FixedMargin(64))
-                .arrange(),
-            )
-            .append(
-                LinearLayout::horizontal(Chain::new(y_axis).append(Text::new(
-                    format!("{}", self.selections[1].value).as_str(),
-                    Point::zero(),
-                    text_style,
-                )))
-                .with_spacing(FixedMargin(64))
-                .arrange(),
-            )
-            .append(
-                LinearLayout::horizontal(Chain::new(z_axis).append(Text::new(
-                    format!("{}", self.selections[2].value).as_str(),
-                    Point::zero(),
-                    text_style,
-                )))
-                .with_spacing(FixedMargin(64))
-                .arrange(),
-            )
-            .append(
-                LinearLayout::horizontal(Chain::new(slider).append(Text::new(
-                    "<   >",
-                    Point::zero(),
-                    text_style,
-                )))
-                .with_spacing(FixedMargin(64))
-                .arrange(),
-            )
-            .append(control),
-        )
-        .with_alignment(horizontal::Center)
-        .arrange()
-        .align_to(&display_area, horizontal::Center, vertical::Center)
-        .draw(&mut self.display)
-        .unwrap();
-
-        Ok(())
     }
 
-    pub fn splash_screen(&mut self, color: Rgb565) {
-        let display_area = self.display.bounding_box();
-        let text_style = MonoTextStyleBuilder::new()
-            .font(&FONT_10X20)
-            .text_color(Rgb565::WHITE)
-            .build();
-
-        let border_style = PrimitiveStyleBuilder::new().fill_color(color).build();
-
-        let text = Text::new("Microlution", Point::zero(), text_style);
-        let border = Rectangle::new(Point::zero(), Size::new(text.size().width, 4))
-            .into_styled(border_style);
-
-        LinearLayout::vertical(Chain::new(text).append(border))
-            .with_spacing(FixedMargin(3))
-            .with_alignment(horizontal::Center)
-            .arrange()
-            .align_to(&display_area, horizontal::Center, vertical::Center)
-            .draw(&mut self.display)
-            .unwrap();
-    }
+    input
+}
 
-    pub async fn increase(&mut self) {
-        if !self.contol_mode {
-            self.selection_idx = self.selection_idx.wrapping_add(1) % self.selections.len() as u32;
-        } else {
-            let axis = match self.selection_idx {
-                0 => OpenflexureAxis::X,
-                1 => OpenflexureAxis::Y,
-                2 => OpenflexureAxis::Z,
-                3 => {
-                    let _response = self
-                        .client
-                        .move_slider(true)
-                        .await
-                        .map_err(|e| error!("failed to move slider {:?}", e));
+/// Sets up the rotary encoder with edge interrupts, falling back to polling
+/// the pins if interrupts are not available.
+fn create_encoder(
+    gpio: &Gpio,
+    decoder: Decoder,
+    direction: &EncoderDirection,
+) -> Box<dyn MenuInput + Send> {
+    let pins = || {
+        (
+            gpio.get(ROTARY_CLK).expect("Invalid CLK pin").into_input(),
+            gpio.get(ROTARY_DT).expect("Invalid DT pin").into_input(),
+            gpio.get(ROTARY_SW).expect("Invalid SW pin").into_input(),
+        )
+    };
 
-                    return;
-                }
-                _ => return,
-            };
-            let _ = self
-                .client
-                .move_openflexure(scope_ui::client::MoveDirection::Pos(axis))
-                .await;
+    let (clk, dt, sw) = pins();
+    match InterruptEncoder::new(gpio, clk, dt, sw, decoder.clone(), direction.clone()) {
+        Ok(encoder) => Box::new(encoder),
+        Err(e) => {
+            warn!(
+                "rotary encoder interrupts unavailable, polling the pins: {}",
+                e
+            );
+            let (clk, dt, sw) = pins();
+            Box::new(RotaryEncoder::new(clk, dt, sw, decoder, direction.clone()))
         }
-
-        // update state
-        let _ = self.setup().await;
     }
+}
 
-    pub async fn decrease(&mut self) {
-        if !self.contol_mode {
-            self.selection_idx = self.selection_idx.wrapping_sub(1) % self.selections.len() as u32;
-        } else {
-            let axis = match self.selection_idx {
-                0 => OpenflexureAxis::X,
-                1 => OpenflexureAxis::Y,
-                2 => OpenflexureAxis::Z,
-                3 => {
-                    let _ = self
-                        .client
-                        .move_slider(false)
-                        .await
-                        .map_err(|e| error!("failed to move slider {:?}", e));
-                    return;
-                }
-                _ => return,
-            };
-            let _ = self
-                .client
-                .move_openflexure(scope_ui::client::MoveDirection::Neg(axis))
-                .await;
+fn create_joystick(config: &JoystickConfig) -> Result<Joystick<SpidevDevice>, std::io::Error> {
+    let mut spi = Spidev::open(JOYSTICK_SPI_DEVICE)?;
+    let options = SpidevOptions::new()
+        .bits_per_word(8)
+        .max_speed_hz(1_000_000)
+        .mode(SpiModeFlags::SPI_MODE_0)
+        .build();
+    spi.configure(&options)?;
 
-            // update state
-            let _ = self.setup().await;
-        }
-    }
+    Ok(Joystick::new(Mcp3008::new(SpidevDevice(spi)), config))
+}
 
-    // pub fn flush(&mut self) -> anyhow::Result<()> {
-    //     self.display.flush().unwrap();
-    //     Ok(())
-    // }
+fn create_spi() -> Result<Spidev, std::io::Error> {
+    let mut spi = Spidev::open("/dev/spidev0.0")?;
+    let options = SpidevOptions::new()
+        .bits_per_word(8)
+        .max_speed_hz(0x07735940)
+        .mode(SpiModeFlags::SPI_MODE_0)
+        .build();
+    spi.configure(&options)?;
 
-    pub fn clear(&mut self) {
-        // self.display.clear(BinaryColor::Off).unwrap();
-        self.display.clear(Rgb565::BLACK).unwrap();
-    }
+    Ok(spi)
 }
@@ -0,0 +1,276 @@
+use std::time::{Duration, Instant};
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+
+/// Frequency of the software PWM driving the backlight LED.
+const BACKLIGHT_PWM_FREQUENCY: f64 = 500.0;
+
+/// Timings and levels used by the [`PowerManager`].
+///
+/// A timeout of `0` disables the respective stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerConfig {
+    /// Seconds without input before the backlight is dimmed.
+    pub dim_after_secs: u64,
+    /// Seconds without input before the panel enters sleep mode.
+    pub sleep_after_secs: u64,
+    /// Backlight level while the display is in use.
+    pub brightness: u8,
+    /// Backlight level while the display is dimmed.
+    pub dim_brightness: u8,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            dim_after_secs: 60,
+            sleep_after_secs: 300,
+            brightness: u8::MAX,
+            dim_brightness: 32,
+        }
+    }
+}
+
+impl PowerConfig {
+    fn dim_after(&self) -> Option<Duration> {
+        (self.dim_after_secs > 0).then(|| Duration::from_secs(self.dim_after_secs))
+    }
+
+    fn sleep_after(&self) -> Option<Duration> {
+        (self.sleep_after_secs > 0).then(|| Duration::from_secs(self.sleep_after_secs))
+    }
+}
+
+/// A backlight whose brightness can be controlled.
+pub trait Backlight {
+    /// Sets the backlight brightness, `0` is off and `255` is full brightness.
+    fn set_level(&mut self, level: u8) -> anyhow::Result<()>;
+}
+
+impl Backlight for rppal::gpio::OutputPin {
+    fn set_level(&mut self, level: u8) -> anyhow::Result<()> {
+        match level {
+            0 => {
+                self.clear_pwm()?;
+                self.set_low();
+            }
+            u8::MAX => {
+                self.clear_pwm()?;
+                self.set_high();
+            }
+            _ => self.set_pwm_frequency(
+                BACKLIGHT_PWM_FREQUENCY,
+                f64::from(level) / f64::from(u8::MAX),
+            )?,
+        }
+        Ok(())
+    }
+}
+
+/// Panels without a controllable backlight, like OLEDs.
+impl Backlight for () {
+    fn set_level(&mut self, _level: u8) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Power state of the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// Backlight at full brightness.
+    Active,
+    /// Backlight dimmed after being idle.
+    Dimmed,
+    /// Backlight off and panel in sleep mode.
+    Sleeping,
+}
+
+/// Dims the backlight and puts the panel to sleep after a period without input.
+///
+/// The manager drives the backlight itself. Entering and leaving sleep mode is
+/// reported to the caller, which owns the panel.
+pub struct PowerManager<B> {
+    backlight: B,
+    config: PowerConfig,
+    state: PowerState,
+    last_activity: Instant,
+}
+
+impl<B> PowerManager<B>
+where
+    B: Backlight,
+{
+    pub fn new(backlight: B, config: PowerConfig, now: Instant) -> Self {
+        let mut manager = Self {
+            backlight,
+            config,
+            state: PowerState::Active,
+            last_activity: now,
+        };
+        manager.apply_backlight();
+        manager
+    }
+
+    pub fn state(&self) -> PowerState {
+        self.state
+    }
+
+    pub fn config(&self) -> &PowerConfig {
+        &self.config
+    }
+
+    /// Replaces the timings and levels, e.g. after they were changed on the
+    /// settings screen. The new brightness is applied right away.
+    pub fn set_config(&mut self, config: PowerConfig) {
+        self.config = config;
+        self.apply_backlight();
+    }
+
+    /// Registers user input and restores full brightness.
+    ///
+    /// Returns the state before the input, so the caller can ignore an event
+    /// that only woke up the display.
+    pub fn activity(&mut self, now: Instant) -> PowerState {
+        let previous = self.state;
+        self.last_activity = now;
+        self.transition(PowerState::Active);
+        previous
+    }
+
+    /// Advances the idle timers. Returns the new state if it changed.
+    pub fn tick(&mut self, now: Instant) -> Option<PowerState> {
+        let idle = now.saturating_duration_since(self.last_activity);
+
+        let target = if self.config.sleep_after().is_some_and(|t| idle >= t) {
+            PowerState::Sleeping
+        } else if self.config.dim_after().is_some_and(|t| idle >= t) {
+            PowerState::Dimmed
+        } else {
+            PowerState::Active
+        };
+
+        self.transition(target).then_some(target)
+    }
+
+    fn transition(&mut self, state: PowerState) -> bool {
+        if self.state == state {
+            return false;
+        }
+
+        debug!("display power state {:?} -> {:?}", self.state, state);
+        self.state = state;
+        self.apply_backlight();
+        true
+    }
+
+    fn apply_backlight(&mut self) {
+        let level = match self.state {
+            PowerState::Active => self.config.brightness,
+            PowerState::Dimmed => self.config.dim_brightness,
+            PowerState::Sleeping => 0,
+        };
+
+        if let Err(e) = self.backlight.set_level(level) {
+            error!("failed to set backlight level {}: {:?}", level, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct DummyBacklight {
+        levels: Vec<u8>,
+    }
+
+    impl Backlight for &mut DummyBacklight {
+        fn set_level(&mut self, level: u8) -> anyhow::Result<()> {
+            self.levels.push(level);
+            Ok(())
+        }
+    }
+
+    fn config() -> PowerConfig {
+        PowerConfig {
+            dim_after_secs: 10,
+            sleep_after_secs: 30,
+            brightness: 200,
+            dim_brightness: 20,
+        }
+    }
+
+    #[test]
+    fn test_dims_and_sleeps_when_idle() {
+        let mut backlight = DummyBacklight::default();
+        let start = Instant::now();
+        {
+            let mut power = PowerManager::new(&mut backlight, config(), start);
+
+            assert_eq!(power.tick(start + Duration::from_secs(5)), None);
+            assert_eq!(
+                power.tick(start + Duration::from_secs(10)),
+                Some(PowerState::Dimmed)
+            );
+            assert_eq!(power.tick(start + Duration::from_secs(20)), None);
+            assert_eq!(
+                power.tick(start + Duration::from_secs(30)),
+                Some(PowerState::Sleeping)
+            );
+        }
+
+        assert_eq!(backlight.levels, vec![200, 20, 0]);
+    }
+
+    #[test]
+    fn test_activity_wakes_up() {
+        let mut backlight = DummyBacklight::default();
+        let start = Instant::now();
+        let mut power = PowerManager::new(&mut backlight, config(), start);
+
+        power.tick(start + Duration::from_secs(40));
+        assert_eq!(power.state(), PowerState::Sleeping);
+
+        let wake = start + Duration::from_secs(41);
+        assert_eq!(power.activity(wake), PowerState::Sleeping);
+        assert_eq!(power.state(), PowerState::Active);
+        assert_eq!(power.tick(wake + Duration::from_secs(9)), None);
+        assert_eq!(power.activity(wake), PowerState::Active);
+    }
+
+    #[test]
+    fn test_zero_timeout_disables_stage() {
+        let mut backlight = DummyBacklight::default();
+        let start = Instant::now();
+        let config = PowerConfig {
+            dim_after_secs: 0,
+            ..config()
+        };
+        let mut power = PowerManager::new(&mut backlight, config, start);
+
+        assert_eq!(power.tick(start + Duration::from_secs(20)), None);
+        assert_eq!(
+            power.tick(start + Duration::from_secs(30)),
+            Some(PowerState::Sleeping)
+        );
+    }
+
+    #[test]
+    fn test_set_config_applies_brightness() {
+        let mut backlight = DummyBacklight::default();
+        let start = Instant::now();
+        {
+            let mut power = PowerManager::new(&mut backlight, config(), start);
+            power.set_config(PowerConfig {
+                brightness: 100,
+                ..config()
+            });
+            assert_eq!(power.config().brightness, 100);
+        }
+
+        assert_eq!(backlight.levels, vec![200, 100]);
+    }
+}
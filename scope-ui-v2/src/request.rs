@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+
+use log::warn;
+
+use crate::{
+    client::{AppClient, MoveDirection, OpenFlexurePosition, OpenflexureAxis},
+    input::button::Action,
+};
+
+/// Long running operation against the servers, run as task outside of the
+/// event loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Reads the stage position
+    RefreshPosition,
+    MoveAxis {
+        axis: OpenflexureAxis,
+        distance: i64,
+    },
+    MoveSlider {
+        up: bool,
+        step_size: i64,
+    },
+    /// Moves the stage in X and Y at once
+    Jog {
+        x: i64,
+        y: i64,
+    },
+    /// Moves the stage to an absolute position
+    MoveTo {
+        x: i64,
+        y: i64,
+        z: i64,
+    },
+    Home,
+    Capture,
+    /// Moves the stage to a position and captures an image there, one
+    /// position of a batch
+    CaptureAt {
+        x: i64,
+        y: i64,
+        z: i64,
+    },
+}
+
+impl Request {
+    /// Merges `other` into this request if both can be sent as one, so
+    /// quickly turning the encoder does not queue up a move per step.
+    fn merge(&mut self, other: &Request) -> bool {
+        match (self, other) {
+            (Self::RefreshPosition, Self::RefreshPosition) => true,
+            (
+                Self::MoveAxis { axis, distance },
+                Self::MoveAxis {
+                    axis: other_axis,
+                    distance: other_distance,
+                },
+            ) if axis == other_axis => {
+                *distance += other_distance;
+                true
+            }
+            (
+                Self::MoveSlider { up, step_size },
+                Self::MoveSlider {
+                    up: other_up,
+                    step_size: other_step_size,
+                },
+            ) if up == other_up => {
+                *step_size += other_step_size;
+                true
+            }
+            // only the last target counts, and only the latest deflection of
+            // the joystick, so the stage stops soon after it is released
+            (this @ Self::MoveTo { .. }, Self::MoveTo { .. })
+            | (this @ Self::Jog { .. }, Self::Jog { .. }) => {
+                *this = other.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the request moves the stage, so the position afterwards can be
+    /// returned to.
+    pub fn is_move(&self) -> bool {
+        matches!(
+            self,
+            Self::MoveAxis { .. }
+                | Self::Jog { .. }
+                | Self::MoveTo { .. }
+                | Self::Home
+                | Self::CaptureAt { .. }
+        )
+    }
+
+    /// Describes the request for error messages, e.g. "failed to move stage".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RefreshPosition => "read stage position",
+            Self::MoveAxis { .. } => "move stage",
+            Self::MoveSlider { .. } => "move slider",
+            Self::Jog { .. } => "jog stage",
+            Self::MoveTo { .. } => "move stage to position",
+            Self::Home => "move stage home",
+            Self::Capture => "capture image",
+            Self::CaptureAt { .. } => "capture image at position",
+        }
+    }
+
+    /// Sends the request. Requests which change the stage position read it
+    /// again afterwards.
+    pub async fn execute(self, client: AppClient) -> anyhow::Result<Option<OpenFlexurePosition>> {
+        match self {
+            Self::RefreshPosition => {}
+            // moves in both directions can cancel each other out when merged
+            Self::MoveAxis { distance: 0, .. } => {}
+            Self::MoveAxis { axis, distance } => {
+                client
+                    .move_openflexure(MoveDirection::Pos(axis), distance)
+                    .await?;
+            }
+            Self::MoveSlider { up, step_size } => {
+                client.move_slider(up, step_size).await?;
+                return Ok(None);
+            }
+            Self::Jog { x, y } => {
+                client.jog_openflexure(x, y).await?;
+            }
+            Self::MoveTo { x, y, z } => {
+                client.move_openflexure_to(x, y, z).await?;
+            }
+            Self::Home => {
+                client.home_openflexure().await?;
+            }
+            Self::Capture => {
+                client.capture_image().await?;
+                return Ok(None);
+            }
+            Self::CaptureAt { x, y, z } => {
+                client.move_openflexure_to(x, y, z).await?;
+                client.capture_image().await?.error_for_status()?;
+            }
+        }
+
+        client.get_openflexure_position().await.map(Some)
+    }
+}
+
+impl TryFrom<Action> for Request {
+    type Error = Action;
+
+    /// Converts the actions which are a single request to the servers. The
+    /// others act on the request queue, see [`Context`](crate::ui::Context).
+    fn try_from(action: Action) -> Result<Self, Self::Error> {
+        match action {
+            Action::Capture => Ok(Self::Capture),
+            Action::Home => Ok(Self::Home),
+            Action::StartBatch | Action::Stop | Action::EmergencyRelease => Err(action),
+        }
+    }
+}
+
+/// Requests waiting to be sent, one request is sent at a time so moves are
+/// not reordered.
+#[derive(Debug, Default)]
+pub struct RequestQueue {
+    queue: VecDeque<Request>,
+    busy: bool,
+}
+
+/// Longest queue before new requests are dropped, e.g. if the server does
+/// not respond
+const MAX_QUEUED: usize = 16;
+
+impl RequestQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `request`, returns `false` if it was dropped because the queue
+    /// is full.
+    pub fn push(&mut self, request: Request) -> bool {
+        if let Some(last) = self.queue.back_mut()
+            && last.merge(&request)
+        {
+            return true;
+        }
+        if self.queue.len() >= MAX_QUEUED {
+            warn!("too many pending requests, dropping {:?}", request);
+            return false;
+        }
+        self.queue.push_back(request);
+        true
+    }
+
+    /// Returns the next request to send if no request is running.
+    pub fn start_next(&mut self) -> Option<Request> {
+        if self.busy {
+            return None;
+        }
+        let request = self.queue.pop_front()?;
+        self.busy = true;
+        Some(request)
+    }
+
+    /// Drops the requests which were not sent yet and returns how many. The
+    /// running request still finishes.
+    pub fn clear(&mut self) -> usize {
+        let dropped = self.queue.len();
+        self.queue.clear();
+        dropped
+    }
+
+    /// Marks the running request as finished.
+    pub fn finish(&mut self) {
+        self.busy = false;
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moves_are_merged_while_busy() {
+        let mut queue = RequestQueue::new();
+        let step = |distance| Request::MoveAxis {
+            axis: OpenflexureAxis::X,
+            distance,
+        };
+
+        queue.push(step(200));
+        assert_eq!(queue.start_next(), Some(step(200)));
+
+        queue.push(step(200));
+        queue.push(step(-20));
+        queue.push(Request::MoveAxis {
+            axis: OpenflexureAxis::Y,
+            distance: 200,
+        });
+        queue.push(step(200));
+        assert_eq!(queue.start_next(), None);
+
+        queue.finish();
+        assert_eq!(queue.start_next(), Some(step(180)));
+        queue.finish();
+        assert_eq!(
+            queue.start_next(),
+            Some(Request::MoveAxis {
+                axis: OpenflexureAxis::Y,
+                distance: 200
+            })
+        );
+        queue.finish();
+        assert_eq!(queue.start_next(), Some(step(200)));
+        queue.finish();
+        assert_eq!(queue.start_next(), None);
+        assert!(!queue.is_busy());
+    }
+
+    #[test]
+    fn test_move_to_keeps_last_target() {
+        let mut queue = RequestQueue::new();
+        queue.push(Request::RefreshPosition);
+        queue.push(Request::MoveTo { x: 1, y: 2, z: 3 });
+        queue.push(Request::MoveTo { x: 4, y: 5, z: 6 });
+
+        assert_eq!(queue.start_next(), Some(Request::RefreshPosition));
+        queue.finish();
+        assert_eq!(
+            queue.start_next(),
+            Some(Request::MoveTo { x: 4, y: 5, z: 6 })
+        );
+        queue.finish();
+        assert_eq!(queue.start_next(), None);
+    }
+
+    #[test]
+    fn test_jog_released_while_busy() {
+        let mut queue = RequestQueue::new();
+        let jog = |x| Request::Jog { x, y: 0 };
+        queue.push(jog(500));
+        assert_eq!(queue.start_next(), Some(jog(500)));
+
+        // the stick is held while the first jog runs, then released
+        for x in [500, 500, 400, 100] {
+            queue.push(jog(x));
+        }
+        queue.finish();
+        assert_eq!(queue.start_next(), Some(jog(100)));
+        queue.finish();
+        assert_eq!(queue.start_next(), None);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut queue = RequestQueue::new();
+        queue.push(Request::Home);
+        queue.push(Request::MoveTo { x: 1, y: 2, z: 3 });
+        queue.push(Request::Capture);
+        assert_eq!(queue.start_next(), Some(Request::Home));
+
+        assert_eq!(queue.clear(), 2);
+        assert!(queue.is_busy());
+        queue.finish();
+        assert_eq!(queue.start_next(), None);
+    }
+
+    #[test]
+    fn test_actions_as_requests() {
+        assert_eq!(Request::try_from(Action::Capture), Ok(Request::Capture));
+        assert_eq!(Request::try_from(Action::Stop), Err(Action::Stop));
+    }
+}
@@ -0,0 +1,111 @@
+use embedded_graphics::{
+    mono_font::{MonoFont, ascii::FONT_10X20, iso_8859_1::FONT_6X10, iso_8859_3::FONT_9X18_BOLD},
+    pixelcolor::{BinaryColor, Rgb565},
+    prelude::*,
+};
+
+use crate::display::PanelModel;
+
+/// Colors and fonts used to draw the UI.
+///
+/// The UI is always drawn in [`Rgb565`]. Monochrome panels map these colors to
+/// [`BinaryColor`] with [`Theme::to_binary`].
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub background: Rgb565,
+    pub text: Rgb565,
+    /// Inactive or secondary text
+    pub muted: Rgb565,
+    /// Highlight for active states like the control mode
+    pub accent: Rgb565,
+    pub selection_background: Rgb565,
+    pub selection_text: Rgb565,
+    /// Marker in front of the selected item
+    pub selection_marker: Rgb565,
+    pub font: &'static MonoFont<'static>,
+    pub title_font: &'static MonoFont<'static>,
+    pub border_width: u32,
+    /// Space between the border and the content
+    pub margin: u32,
+    /// Vertical space between two menu rows
+    pub row_spacing: u32,
+}
+
+impl Theme {
+    /// Theme for the color TFT panels.
+    pub const fn color() -> Self {
+        Self {
+            background: Rgb565::BLACK,
+            text: Rgb565::WHITE,
+            muted: Rgb565::CSS_GRAY,
+            accent: Rgb565::CSS_ORANGE,
+            selection_background: Rgb565::BLACK,
+            selection_text: Rgb565::WHITE,
+            selection_marker: Rgb565::CSS_ORANGE,
+            font: &FONT_9X18_BOLD,
+            title_font: &FONT_10X20,
+            border_width: 3,
+            margin: 4,
+            row_spacing: 4,
+        }
+    }
+
+    /// Theme for monochrome panels, selected items are drawn inverted.
+    pub const fn monochrome() -> Self {
+        Self {
+            background: Rgb565::BLACK,
+            text: Rgb565::WHITE,
+            muted: Rgb565::WHITE,
+            accent: Rgb565::WHITE,
+            selection_background: Rgb565::WHITE,
+            selection_text: Rgb565::BLACK,
+            selection_marker: Rgb565::BLACK,
+            font: &FONT_6X10,
+            title_font: &FONT_10X20,
+            border_width: 1,
+            margin: 1,
+            row_spacing: 0,
+        }
+    }
+
+    pub fn for_panel(model: PanelModel) -> Self {
+        match model {
+            PanelModel::Ssd1306 => Self::monochrome(),
+            PanelModel::Ili9341 | PanelModel::St7735 | PanelModel::St7789 => Self::color(),
+        }
+    }
+
+    /// Maps a color of this theme to a monochrome pixel.
+    ///
+    /// The background and the text of selected items are off, everything else
+    /// is lit, so a selection shows up as inverted row.
+    pub fn to_binary(&self, color: Rgb565) -> BinaryColor {
+        if color == self.background || color == self.selection_text {
+            BinaryColor::Off
+        } else {
+            BinaryColor::On
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::color()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monochrome_selection_is_inverted() {
+        let theme = Theme::monochrome();
+
+        assert_eq!(theme.to_binary(theme.background), BinaryColor::Off);
+        assert_eq!(theme.to_binary(theme.text), BinaryColor::On);
+        assert_eq!(theme.to_binary(theme.selection_background), BinaryColor::On);
+        assert_eq!(theme.to_binary(theme.selection_text), BinaryColor::Off);
+        assert_eq!(theme.to_binary(theme.selection_marker), BinaryColor::Off);
+    }
+}
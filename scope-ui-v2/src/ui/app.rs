@@ -0,0 +1,212 @@
+use std::{path::PathBuf, time::Instant};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyleBuilder,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyleBuilder, Rectangle},
+    text::Text,
+};
+use embedded_layout::{
+    align::{Align, horizontal, vertical},
+    layout::linear::{FixedMargin, LinearLayout},
+    prelude::*,
+};
+use log::{error, info};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+    Context, ScreenStack, content_area,
+    control::ControlScreen,
+    display_error,
+    widgets::{Toast, Widget},
+};
+use crate::{
+    client::OpenFlexurePosition,
+    config::AppConfig,
+    display::Panel,
+    input::{InputEvent, button::Action},
+    logging::LogBuffer,
+    request::Request,
+    theme::Theme,
+};
+
+/// Events handled by the event loop.
+#[derive(Debug)]
+pub enum AppEvent {
+    Input(InputEvent),
+    /// Result of a request, sent by the task running it
+    Completed {
+        request: Request,
+        result: anyhow::Result<Option<OpenFlexurePosition>>,
+    },
+}
+
+/// The UI, showing the top screen of the screen stack.
+pub struct App<D>
+where
+    D: Panel,
+{
+    ctx: Context<D>,
+    screens: ScreenStack<D>,
+    /// The screen changed since it was drawn last
+    dirty: bool,
+}
+
+impl<D> Drop for App<D>
+where
+    D: Panel,
+{
+    fn drop(&mut self) {
+        self.screens.exit(&mut self.ctx);
+        self.ctx.clear();
+        self.splash_screen(self.ctx.theme.muted);
+    }
+}
+
+impl<D> App<D>
+where
+    D: Panel,
+{
+    pub fn new(
+        config: AppConfig,
+        config_path: PathBuf,
+        theme: Theme,
+        display: D,
+        log: LogBuffer,
+        events: UnboundedSender<AppEvent>,
+    ) -> Self {
+        let ctx = Context::new(display, config, config_path, theme, log, events);
+        Self {
+            ctx,
+            screens: ScreenStack::new(Box::new(ControlScreen::new())),
+            dirty: true,
+        }
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.ctx.config
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Queues a request to the servers.
+    pub fn request(&mut self, request: Request) {
+        self.ctx.request(request);
+    }
+
+    /// Passes the event to the shown screen. Refreshing the position and
+    /// bound actions work on all screens.
+    pub fn handle(&mut self, event: InputEvent) {
+        match event {
+            // refresh the positions, e.g. after the stage was moved elsewhere
+            InputEvent::DoubleClick => self.request(Request::RefreshPosition),
+            InputEvent::Action(action) => self.action(action),
+            event => {
+                let transition = self.screens.top().handle(event, &mut self.ctx);
+                self.screens.apply(transition, &mut self.ctx);
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Runs an action triggered by a bound button or gesture.
+    fn action(&mut self, action: Action) {
+        info!("action {:?}", action);
+        match Request::try_from(action) {
+            Ok(request) => self.request(request),
+            Err(Action::Stop) => self.ctx.stop(),
+            Err(Action::EmergencyRelease) => self.ctx.emergency_stop(),
+            Err(Action::StartBatch) => self.ctx.start_batch(),
+            Err(action) => unreachable!("action {:?} is a request", action),
+        }
+    }
+
+    /// Handles the result of a request.
+    pub fn completed(
+        &mut self,
+        request: Request,
+        result: anyhow::Result<Option<OpenFlexurePosition>>,
+    ) {
+        if self.ctx.completed(request, result) {
+            self.dirty = true;
+        }
+    }
+
+    /// Called periodically, e.g. so the log console shows new lines.
+    pub fn tick(&mut self, now: Instant) {
+        if self.screens.top().tick(&mut self.ctx) {
+            self.dirty = true;
+        }
+        if let Some((_, until)) = &self.ctx.toast
+            && *until <= now
+        {
+            // screens only drawing changes have to draw everything again
+            self.ctx.toast = None;
+            self.screens.top().on_enter(&mut self.ctx);
+            self.dirty = true;
+        }
+    }
+
+    /// Draws the shown screen and the toast over it.
+    pub fn draw(&mut self) -> anyhow::Result<()> {
+        self.dirty = false;
+        self.screens.top().render(&mut self.ctx)?;
+
+        if let Some((message, _)) = &self.ctx.toast {
+            Toast::new(message)
+                .draw(
+                    content_area(&self.ctx),
+                    &self.ctx.theme,
+                    &mut self.ctx.display,
+                )
+                .map_err(display_error)?;
+            self.ctx.display.flush().map_err(display_error)?;
+        }
+        Ok(())
+    }
+
+    pub fn splash_screen(&mut self, color: Rgb565) {
+        let display = &mut self.ctx.display;
+        let display_area = display.bounding_box();
+        let text_style = MonoTextStyleBuilder::new()
+            .font(self.ctx.theme.title_font)
+            .text_color(self.ctx.theme.text)
+            .build();
+
+        let border_style = PrimitiveStyleBuilder::new().fill_color(color).build();
+
+        let text = Text::new("Microlution", Point::zero(), text_style);
+        let border = Rectangle::new(Point::zero(), Size::new(text.size().width, 4))
+            .into_styled(border_style);
+
+        LinearLayout::vertical(Chain::new(text).append(border))
+            .with_spacing(FixedMargin(3))
+            .with_alignment(horizontal::Center)
+            .arrange()
+            .align_to(&display_area, horizontal::Center, vertical::Center)
+            .draw(display)
+            .unwrap();
+        let _ = display.flush();
+    }
+
+    pub fn sleep(&mut self) {
+        if let Err(e) = self.ctx.display.set_sleep(true) {
+            error!("failed to put display to sleep: {:?}", e);
+        }
+    }
+
+    pub fn wake(&mut self) {
+        if let Err(e) = self.ctx.display.set_sleep(false) {
+            error!("failed to wake up display: {:?}", e);
+        }
+        self.screens.top().on_enter(&mut self.ctx);
+        self.dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.ctx.clear();
+    }
+}
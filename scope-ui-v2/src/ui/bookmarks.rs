@@ -0,0 +1,239 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use super::{
+    Context, Screen, Transition, content_area, display_error, draw_frame, draw_title,
+    step_selection,
+    widgets::{
+        ConfirmScreen, EntryOutcome, List, Regions, TextEntry, ValueRow, Widget, row_height,
+    },
+};
+use crate::{client::OpenflexureAxis, display::Panel, input::InputEvent, request::Request};
+
+/// Longest name of a bookmark, so it fits next to the position
+const NAME_LEN: usize = 8;
+
+/// Rows of the bookmarks screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    /// Saves the position under the next free name
+    SaveHere,
+    /// Saves the position under an entered name
+    SaveAs,
+    /// Switches between recalling and deleting bookmarks
+    Action,
+    Bookmark(usize),
+    Back,
+}
+
+/// Saved stage positions. Selecting a bookmark moves the stage there or
+/// deletes it, depending on the action row.
+pub struct BookmarksScreen {
+    selection_idx: u32,
+    delete: bool,
+    /// Name of the bookmark which is entered
+    editing: Option<TextEntry>,
+    regions: Regions,
+}
+
+impl BookmarksScreen {
+    pub fn new() -> Self {
+        Self {
+            selection_idx: 0,
+            delete: false,
+            editing: None,
+            regions: Regions::new(),
+        }
+    }
+
+    fn rows<D>(ctx: &Context<D>) -> Vec<Row> {
+        let bookmarks = (0..ctx.config.bookmarks.len()).map(Row::Bookmark);
+        [Row::SaveHere, Row::SaveAs, Row::Action]
+            .into_iter()
+            .chain(bookmarks)
+            .chain([Row::Back])
+            .collect()
+    }
+
+    fn save<D>(ctx: &mut Context<D>, name: String)
+    where
+        D: Panel,
+    {
+        ctx.config.set_bookmark(name.clone(), ctx.position);
+        ctx.save_config();
+        ctx.toast(format!("{} {}", ctx.tr("Saved"), name));
+    }
+
+    /// Handles the input while a name is entered, the bookmark is saved
+    /// once it is complete.
+    fn handle_entry<D>(&mut self, event: InputEvent, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let Some(entry) = &mut self.editing else {
+            return;
+        };
+        match entry.handle(event) {
+            EntryOutcome::Editing => return,
+            EntryOutcome::Done => {
+                let name = entry.text();
+                if !name.is_empty() {
+                    Self::save(ctx, name);
+                }
+            }
+            EntryOutcome::Cancelled => {}
+        }
+        self.editing = None;
+        self.regions.invalidate();
+    }
+
+    fn select<D>(&mut self, row: Row, ctx: &mut Context<D>) -> Transition<D>
+    where
+        D: Panel,
+    {
+        match row {
+            Row::SaveHere => Self::save(ctx, ctx.config.next_bookmark_name()),
+            Row::SaveAs => {
+                self.editing = Some(TextEntry::new(&ctx.config.next_bookmark_name(), NAME_LEN));
+                self.regions.invalidate();
+            }
+            Row::Action => self.delete = !self.delete,
+            Row::Bookmark(idx) if self.delete => {
+                let name = ctx.config.bookmarks[idx].name.clone();
+                let message = format!("{} {}?", ctx.tr("Delete"), name);
+                return Transition::Push(Box::new(ConfirmScreen::new(
+                    ctx.tr("Delete"),
+                    message,
+                    move |ctx: &mut Context<D>| {
+                        ctx.config
+                            .bookmarks
+                            .retain(|bookmark| bookmark.name != name);
+                        ctx.save_config();
+                    },
+                )));
+            }
+            Row::Bookmark(idx) => {
+                let position = ctx.config.bookmarks[idx].position();
+                ctx.request(Request::MoveTo {
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                });
+            }
+            Row::Back => return Transition::Pop,
+        }
+        Transition::Stay
+    }
+
+    fn value_text<D>(&self, row: Row, ctx: &Context<D>) -> String
+    where
+        D: Panel,
+    {
+        match row {
+            Row::SaveHere => ctx.config.next_bookmark_name(),
+            Row::SaveAs => ">".to_string(),
+            Row::Action if self.delete => ctx.tr("Delete").to_string(),
+            Row::Action => ctx.tr("Go to").to_string(),
+            Row::Bookmark(idx) => {
+                let position = ctx.config.bookmarks[idx].position();
+                let values: Vec<_> = OpenflexureAxis::ALL
+                    .into_iter()
+                    .map(|axis| ctx.config.units.format(axis, position.axis(axis)).0)
+                    .collect();
+                values.join(",")
+            }
+            Row::Back => "<".to_string(),
+        }
+    }
+}
+
+impl Default for BookmarksScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Screen<D> for BookmarksScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "bookmarks"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        if self.editing.is_some() {
+            self.handle_entry(event, ctx);
+            return Transition::Stay;
+        }
+
+        let rows = Self::rows(ctx);
+        match event {
+            InputEvent::Up => {
+                self.selection_idx = step_selection(self.selection_idx, rows.len(), true);
+            }
+            InputEvent::Down => {
+                self.selection_idx = step_selection(self.selection_idx, rows.len(), false);
+            }
+            InputEvent::Select => return self.select(rows[self.selection_idx as usize], ctx),
+            InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        let area = content_area(ctx);
+        let invalidated = self.regions.take_invalidated();
+        if invalidated {
+            draw_frame(ctx)?;
+        }
+
+        if let Some(entry) = &self.editing {
+            if invalidated {
+                draw_title(ctx, ctx.tr("Name"))?;
+            }
+            let row = Rectangle::with_center(
+                area.center(),
+                Size::new(area.size.width, row_height(&ctx.theme)),
+            );
+            self.regions
+                .draw(entry, row, &ctx.theme, &mut ctx.display)
+                .map_err(display_error)?;
+            return ctx.display.flush().map_err(display_error);
+        }
+
+        let rows = Self::rows(ctx);
+        // a deleted bookmark may have been selected
+        self.selection_idx = self.selection_idx.min(rows.len() as u32 - 1);
+        // copied, so the list does not borrow the config while drawing
+        let texts: Vec<_> = rows
+            .into_iter()
+            .map(|row| {
+                let label = match row {
+                    Row::SaveHere => ctx.tr("Save here").to_string(),
+                    Row::SaveAs => ctx.tr("Save as").to_string(),
+                    Row::Action => ctx.tr("Action").to_string(),
+                    Row::Bookmark(idx) => ctx.config.bookmarks[idx].name.clone(),
+                    Row::Back => ctx.tr("Back").to_string(),
+                };
+                (label, self.value_text(row, ctx))
+            })
+            .collect();
+        let rows: Vec<_> = texts
+            .iter()
+            .map(|(label, value)| ValueRow::new(label, value))
+            .collect();
+
+        List::new(&rows, self.selection_idx)
+            .draw_changed(area, &ctx.theme, &mut ctx.display, &mut self.regions)
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+}
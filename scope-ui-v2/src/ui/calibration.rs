@@ -0,0 +1,285 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use log::error;
+use tokio::sync::oneshot;
+
+use super::{
+    Context, Screen, Transition, content_area, display_error, draw_frame, draw_title,
+    step_selection,
+    widgets::{EntryOutcome, List, NumberEntry, Regions, ValueRow, Widget, row_height},
+};
+use crate::{client::OpenflexureAxis, display::Panel, input::InputEvent, units::StepsPerPixel};
+
+/// Largest steps per mm which can be entered
+const MAX_STEPS_PER_MM: i64 = 999_999;
+/// Largest size of a camera pixel which can be entered, in nm
+const MAX_NM_PER_PIXEL: i64 = 99_999;
+
+/// Rows of the calibration screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    /// Steps per µm of an axis
+    Axis(OpenflexureAxis),
+    /// Size of a camera pixel on the sample
+    PixelSize,
+    /// Calibrates X and Y from the image shift
+    CalibrateXy,
+    Back,
+}
+
+const ROWS: [Row; 6] = [
+    Row::Axis(OpenflexureAxis::X),
+    Row::Axis(OpenflexureAxis::Y),
+    Row::Axis(OpenflexureAxis::Z),
+    Row::PixelSize,
+    Row::CalibrateXy,
+    Row::Back,
+];
+
+impl Row {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Axis(OpenflexureAxis::X) => "X st/µm",
+            Self::Axis(OpenflexureAxis::Y) => "Y st/µm",
+            Self::Axis(OpenflexureAxis::Z) => "Z st/µm",
+            Self::PixelSize => "µm/pixel",
+            Self::CalibrateXy => "Calibrate XY",
+            Self::Back => "Back",
+        }
+    }
+
+    /// Title while the value is entered, in the unit it is entered in
+    fn entry_title(self) -> &'static str {
+        match self {
+            Self::Axis(OpenflexureAxis::X) => "X steps/mm",
+            Self::Axis(OpenflexureAxis::Y) => "Y steps/mm",
+            Self::Axis(OpenflexureAxis::Z) => "Z steps/mm",
+            _ => "nm/pixel",
+        }
+    }
+}
+
+/// Calibration of the distance the axes move per step. X and Y are measured
+/// by OpenFlexure from the image shift of known moves, which is converted
+/// with the size of a camera pixel. Z can not be seen in the image, its
+/// factor is entered like a measured one of X and Y. Factors are entered in
+/// steps per mm and pixel sizes in nm, so they are whole numbers.
+pub struct CalibrationScreen {
+    selection_idx: u32,
+    editing: Option<(Row, NumberEntry)>,
+    /// Result of the running calibration of X and Y
+    calibrating: Option<oneshot::Receiver<anyhow::Result<StepsPerPixel>>>,
+    regions: Regions,
+}
+
+impl CalibrationScreen {
+    pub fn new() -> Self {
+        Self {
+            selection_idx: 0,
+            editing: None,
+            calibrating: None,
+            regions: Regions::new(),
+        }
+    }
+
+    fn start_entry<D>(&mut self, row: Row, ctx: &Context<D>)
+    where
+        D: Panel,
+    {
+        let units = &ctx.config.units;
+        let entry = match row {
+            Row::Axis(axis) => {
+                let steps_per_mm = units.steps_per_um.axis(axis).unwrap_or(0.0) * 1000.0;
+                NumberEntry::new(steps_per_mm.round() as i64, 0, MAX_STEPS_PER_MM)
+            }
+            _ => {
+                let nm = units.um_per_pixel.unwrap_or(0.0) * 1000.0;
+                NumberEntry::new(nm.round() as i64, 0, MAX_NM_PER_PIXEL)
+            }
+        };
+        self.editing = Some((row, entry));
+        self.regions.invalidate();
+    }
+
+    /// Handles the input while a number is entered, it is stored once it is
+    /// complete. `0` clears the calibration.
+    fn handle_entry<D>(&mut self, event: InputEvent, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let Some((row, entry)) = &mut self.editing else {
+            return;
+        };
+        match entry.handle(event) {
+            EntryOutcome::Editing => return,
+            EntryOutcome::Done => {
+                let value = (entry.value() > 0).then(|| entry.value() as f64 / 1000.0);
+                let units = &mut ctx.config.units;
+                match *row {
+                    Row::Axis(axis) => units.steps_per_um.set_axis(axis, value),
+                    _ => units.um_per_pixel = value,
+                }
+                ctx.save_config();
+            }
+            EntryOutcome::Cancelled => {}
+        }
+        self.editing = None;
+        self.regions.invalidate();
+    }
+
+    fn calibrate_xy<D>(&mut self, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        if self.calibrating.is_some() {
+            return;
+        }
+        if ctx.config.units.um_per_pixel.is_none() {
+            ctx.toast(ctx.tr("Set µm/pixel first"));
+            return;
+        }
+
+        let client = ctx.client().clone();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            // the screen may be closed in the meantime
+            let _ = tx.send(client.calibrate_camera_stage_mapping().await);
+        });
+        self.calibrating = Some(rx);
+    }
+
+    /// Stores the factors of X and Y once the calibration finished.
+    fn finish_calibration<D>(&mut self, result: anyhow::Result<StepsPerPixel>, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let units = &mut ctx.config.units;
+        match (result, units.um_per_pixel) {
+            (Ok(steps), Some(um_per_pixel)) => {
+                let (x, y) = steps.steps_per_um(um_per_pixel);
+                units.steps_per_um.x = Some(x);
+                units.steps_per_um.y = Some(y);
+                ctx.save_config();
+                ctx.toast(ctx.tr("Calibrated"));
+            }
+            (Ok(_), None) => {}
+            (Err(e), _) => {
+                error!("failed to calibrate the stage {:?}", e);
+                ctx.toast(ctx.tr("Calibration failed"));
+            }
+        }
+    }
+
+    fn value_text<D>(&self, row: Row, ctx: &Context<D>) -> String
+    where
+        D: Panel,
+    {
+        let units = &ctx.config.units;
+        match row {
+            Row::Axis(axis) => units
+                .steps_per_um
+                .axis(axis)
+                .map_or_else(|| "-".to_string(), |scale| format!("{:.3}", scale)),
+            Row::PixelSize => units
+                .um_per_pixel
+                .map_or_else(|| "-".to_string(), |size| format!("{:.3}", size)),
+            Row::CalibrateXy if self.calibrating.is_some() => "...".to_string(),
+            Row::CalibrateXy => ">".to_string(),
+            Row::Back => "<".to_string(),
+        }
+    }
+}
+
+impl Default for CalibrationScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Screen<D> for CalibrationScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "calibration"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        if self.editing.is_some() {
+            self.handle_entry(event, ctx);
+            return Transition::Stay;
+        }
+
+        match event {
+            InputEvent::Up => {
+                self.selection_idx = step_selection(self.selection_idx, ROWS.len(), true);
+            }
+            InputEvent::Down => {
+                self.selection_idx = step_selection(self.selection_idx, ROWS.len(), false);
+            }
+            InputEvent::Select => match ROWS[self.selection_idx as usize] {
+                row @ (Row::Axis(_) | Row::PixelSize) => self.start_entry(row, ctx),
+                Row::CalibrateXy => self.calibrate_xy(ctx),
+                Row::Back => return Transition::Pop,
+            },
+            InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        let area = content_area(ctx);
+        let invalidated = self.regions.take_invalidated();
+        if invalidated {
+            draw_frame(ctx)?;
+        }
+
+        if let Some((row, entry)) = &self.editing {
+            if invalidated {
+                draw_title(ctx, ctx.tr(row.entry_title()))?;
+            }
+            let row = Rectangle::with_center(
+                area.center(),
+                Size::new(area.size.width, row_height(&ctx.theme)),
+            );
+            self.regions
+                .draw(entry, row, &ctx.theme, &mut ctx.display)
+                .map_err(display_error)?;
+            return ctx.display.flush().map_err(display_error);
+        }
+
+        let rows: Vec<_> = ROWS
+            .iter()
+            .map(|row| ValueRow::new(ctx.tr(row.name()), self.value_text(*row, ctx)))
+            .collect();
+
+        List::new(&rows, self.selection_idx)
+            .draw_changed(area, &ctx.theme, &mut ctx.display, &mut self.regions)
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+
+    /// Redraws when the calibration finished.
+    fn tick(&mut self, ctx: &mut Context<D>) -> bool {
+        let Some(calibrating) = &mut self.calibrating else {
+            return false;
+        };
+        let result = match calibrating.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::error::TryRecvError::Empty) => return false,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                Err(anyhow::anyhow!("Calibration task stopped"))
+            }
+        };
+        self.calibrating = None;
+        self.finish_calibration(result, ctx);
+        true
+    }
+}
@@ -0,0 +1,271 @@
+use log::debug;
+
+use super::{
+    Context, Screen, Transition,
+    bookmarks::BookmarksScreen,
+    content_area, display_error, draw_frame,
+    goto::GotoScreen,
+    info::InfoScreen,
+    log_view::LogScreen,
+    settings::SettingsScreen,
+    step_selection,
+    widgets::{List, Regions, ValueRow, Widget},
+};
+use crate::{
+    client::OpenflexureAxis, diagnostics::SystemInfo, display::Panel, input::InputEvent,
+    input::joystick::JOG_MAX, limits::Limit, request::Request, units::Unit,
+};
+
+/// Entries of the control screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Axis(OpenflexureAxis),
+    Slider,
+    /// Moves back to the position before the last move
+    Undo,
+    Goto,
+    Bookmarks,
+    Settings,
+    Info,
+    Log,
+}
+
+const ENTRIES: [Entry; 10] = [
+    Entry::Axis(OpenflexureAxis::X),
+    Entry::Axis(OpenflexureAxis::Y),
+    Entry::Axis(OpenflexureAxis::Z),
+    Entry::Slider,
+    Entry::Undo,
+    Entry::Goto,
+    Entry::Bookmarks,
+    Entry::Settings,
+    Entry::Info,
+    Entry::Log,
+];
+
+impl Entry {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Axis(OpenflexureAxis::X) => "X Axis",
+            Self::Axis(OpenflexureAxis::Y) => "Y Axis",
+            Self::Axis(OpenflexureAxis::Z) => "Z Axis",
+            Self::Slider => "Slider",
+            Self::Undo => "Undo",
+            Self::Goto => "Go to",
+            Self::Bookmarks => "Bookmarks",
+            Self::Settings => "Settings",
+            Self::Info => "Info",
+            Self::Log => "Log",
+        }
+    }
+}
+
+/// Main screen, moving the stage and the slider. Selecting an axis or the
+/// slider switches to the control mode in which the encoder moves it.
+pub struct ControlScreen {
+    selection_idx: u32,
+    control_mode: bool,
+    regions: Regions,
+}
+
+impl ControlScreen {
+    pub fn new() -> Self {
+        Self {
+            selection_idx: 0,
+            control_mode: false,
+            regions: Regions::new(),
+        }
+    }
+
+    fn selected(&self) -> Entry {
+        ENTRIES[self.selection_idx as usize]
+    }
+
+    fn trigger_control_mode(&mut self) {
+        self.control_mode = !self.control_mode;
+        debug!("switch control mode to {}", self.control_mode);
+    }
+
+    fn step<D>(&mut self, up: bool, fine: bool, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        if !self.control_mode {
+            self.selection_idx = step_selection(self.selection_idx, ENTRIES.len(), up);
+            return;
+        }
+
+        match self.selected() {
+            Entry::Axis(axis) => {
+                let step_size = ctx.config.step_distance(axis, fine);
+                let distance = if up { step_size } else { -step_size };
+                ctx.request(Request::MoveAxis { axis, distance });
+            }
+            Entry::Slider => {
+                // the slider is not calibrated, it always moves in steps
+                let steps = &ctx.config.steps;
+                let step_size = if fine {
+                    steps.fine_step_size
+                } else {
+                    steps.step_size
+                };
+                ctx.request(Request::MoveSlider { up, step_size });
+            }
+            Entry::Undo
+            | Entry::Goto
+            | Entry::Bookmarks
+            | Entry::Settings
+            | Entry::Info
+            | Entry::Log => {}
+        }
+    }
+
+    /// Moves the stage in X and Y proportionally to the deflection of the
+    /// joystick.
+    fn jog<D>(&mut self, x: i16, y: i16, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let max_step = ctx.config.input.joystick.max_step;
+        let scale = |deflection: i16| i64::from(deflection) * max_step / i64::from(JOG_MAX);
+        let (x, y) = (scale(x), scale(y));
+        if x != 0 || y != 0 {
+            ctx.request(Request::Jog { x, y });
+        }
+    }
+
+    /// Unit of an axis, marked if the axis is at one of its soft limits,
+    /// e.g. `µm max`. Steps are shown without a unit.
+    fn unit_text<D>(entry: Entry, ctx: &Context<D>) -> Option<String> {
+        let Entry::Axis(axis) = entry else {
+            return None;
+        };
+        let steps = ctx.position.axis(axis);
+        let units = &ctx.config.units;
+        let unit = (units.unit != Unit::Steps).then(|| units.format(axis, steps).1);
+        let limit = match ctx.config.limits.at_limit(axis, steps) {
+            Some(Limit::Min) => Some("min"),
+            Some(Limit::Max) => Some("max"),
+            None => None,
+        };
+        let parts: Vec<_> = unit.into_iter().chain(limit).collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    fn value_text<D>(entry: Entry, ctx: &Context<D>) -> String
+    where
+        D: Panel,
+    {
+        match entry {
+            Entry::Axis(axis) => ctx.config.units.format(axis, ctx.position.axis(axis)).0,
+            Entry::Slider => "<  >".to_string(),
+            Entry::Undo => match ctx.undo_steps() {
+                0 => "-".to_string(),
+                steps => steps.to_string(),
+            },
+            Entry::Goto | Entry::Bookmarks | Entry::Settings | Entry::Info | Entry::Log => {
+                ">".to_string()
+            }
+        }
+    }
+}
+
+impl Default for ControlScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Screen<D> for ControlScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "control"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        match event {
+            InputEvent::Up => self.step(true, false, ctx),
+            InputEvent::Down => self.step(false, false, ctx),
+            // small steps while the button is held
+            InputEvent::FineUp if self.control_mode => self.step(true, true, ctx),
+            InputEvent::FineDown if self.control_mode => self.step(false, true, ctx),
+            InputEvent::Jog { x, y } => self.jog(x, y, ctx),
+            InputEvent::Select => match self.selected() {
+                Entry::Undo if !self.control_mode => ctx.undo(),
+                Entry::Goto if !self.control_mode => {
+                    return Transition::Push(Box::new(GotoScreen::new(ctx.position)));
+                }
+                Entry::Bookmarks if !self.control_mode => {
+                    return Transition::Push(Box::new(BookmarksScreen::new()));
+                }
+                Entry::Settings if !self.control_mode => {
+                    return Transition::Push(Box::new(SettingsScreen::new()));
+                }
+                Entry::Info if !self.control_mode => {
+                    return Transition::Push(Box::new(InfoScreen::new(SystemInfo::new("/"))));
+                }
+                Entry::Log if !self.control_mode => {
+                    return Transition::Push(Box::new(LogScreen::new(ctx.log.clone())));
+                }
+                _ => self.trigger_control_mode(),
+            },
+            InputEvent::LongPress if self.control_mode => self.trigger_control_mode(),
+            // shortcut to get back after moving away by accident
+            InputEvent::LongPress => ctx.undo(),
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        if self.regions.take_invalidated() {
+            draw_frame(ctx)?;
+        }
+        let units: Vec<_> = ENTRIES
+            .iter()
+            .map(|entry| Self::unit_text(*entry, ctx))
+            .collect();
+        let rows: Vec<_> = ENTRIES
+            .iter()
+            .zip(&units)
+            .map(|(entry, unit)| {
+                let row = ValueRow::new(ctx.tr(entry.name()), Self::value_text(*entry, ctx));
+                match unit {
+                    Some(unit) => row.with_unit(unit),
+                    None => row,
+                }
+            })
+            .collect();
+
+        let control_color = if self.control_mode {
+            ctx.theme.accent
+        } else {
+            ctx.theme.muted
+        };
+        let control_txt = format!(
+            "{}: {}",
+            ctx.tr("Control Mode"),
+            ctx.tr(if self.control_mode { "On" } else { "Off" })
+        );
+
+        List::new(&rows, self.selection_idx)
+            .with_footer(&control_txt, control_color)
+            .draw_changed(
+                content_area(ctx),
+                &ctx.theme,
+                &mut ctx.display,
+                &mut self.regions,
+            )
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+}
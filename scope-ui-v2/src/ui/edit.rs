@@ -0,0 +1,79 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use super::{
+    Context, Screen, Transition, content_area, display_error, draw_frame, draw_title,
+    widgets::{Regions, Spinner, row_height},
+};
+use crate::{display::Panel, input::InputEvent};
+
+type EditAction<D> = Box<dyn FnOnce(&mut Context<D>, i64)>;
+
+/// Screen changing a number with the encoder. Select stores the value, a
+/// long press cancels.
+pub struct EditScreen<D> {
+    title: &'static str,
+    spinner: Spinner,
+    on_done: Option<EditAction<D>>,
+    regions: Regions,
+}
+
+impl<D> EditScreen<D> {
+    pub fn new(
+        title: &'static str,
+        spinner: Spinner,
+        on_done: impl FnOnce(&mut Context<D>, i64) + 'static,
+    ) -> Self {
+        Self {
+            title,
+            spinner,
+            on_done: Some(Box::new(on_done)),
+            regions: Regions::new(),
+        }
+    }
+}
+
+impl<D> Screen<D> for EditScreen<D>
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "edit"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        match event {
+            InputEvent::Up | InputEvent::FineUp => self.spinner.step(true),
+            InputEvent::Down | InputEvent::FineDown => self.spinner.step(false),
+            InputEvent::Select => {
+                if let Some(on_done) = self.on_done.take() {
+                    on_done(ctx, self.spinner.value);
+                }
+                return Transition::Pop;
+            }
+            InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        let area = content_area(ctx);
+        if self.regions.take_invalidated() {
+            draw_frame(ctx)?;
+            draw_title(ctx, ctx.tr(self.title))?;
+        }
+
+        let row = Rectangle::with_center(
+            area.center(),
+            Size::new(area.size.width, row_height(&ctx.theme)),
+        );
+        self.regions
+            .draw(&self.spinner, row, &ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+}
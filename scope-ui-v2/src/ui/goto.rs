@@ -0,0 +1,233 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use super::{
+    Context, Screen, Transition, content_area, display_error, draw_frame, draw_title,
+    step_selection,
+    widgets::{EntryOutcome, List, NumberEntry, Regions, ValueRow, Widget, row_height},
+};
+use crate::{
+    client::{OpenFlexurePosition, OpenflexureAxis},
+    display::Panel,
+    input::InputEvent,
+    request::Request,
+};
+
+/// Largest distance from the origin which can be entered, in motor steps
+const MAX_TARGET: i64 = 100_000;
+
+/// Entries of the go to screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Axis(OpenflexureAxis),
+    Move,
+    Back,
+}
+
+const FIELDS: [Field; 5] = [
+    Field::Axis(OpenflexureAxis::X),
+    Field::Axis(OpenflexureAxis::Y),
+    Field::Axis(OpenflexureAxis::Z),
+    Field::Move,
+    Field::Back,
+];
+
+impl Field {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Axis(OpenflexureAxis::X) => "X Axis",
+            Self::Axis(OpenflexureAxis::Y) => "Y Axis",
+            Self::Axis(OpenflexureAxis::Z) => "Z Axis",
+            Self::Move => "Move",
+            Self::Back => "Back",
+        }
+    }
+}
+
+fn target_title(axis: OpenflexureAxis) -> &'static str {
+    match axis {
+        OpenflexureAxis::X => "X target",
+        OpenflexureAxis::Y => "Y target",
+        OpenflexureAxis::Z => "Z target",
+    }
+}
+
+/// Moves the stage to an absolute position. The target of every axis is
+/// entered digit by digit, starting at the current position. Axes shown in
+/// µm or mm are entered in µm.
+pub struct GotoScreen {
+    target: OpenFlexurePosition,
+    selection_idx: u32,
+    /// Axis whose target is entered
+    editing: Option<(OpenflexureAxis, NumberEntry)>,
+    /// A request was running at the last tick
+    busy: bool,
+    regions: Regions,
+}
+
+impl GotoScreen {
+    pub fn new(position: OpenFlexurePosition) -> Self {
+        Self {
+            target: position,
+            selection_idx: 0,
+            editing: None,
+            busy: false,
+            regions: Regions::new(),
+        }
+    }
+
+    fn set_target(&mut self, axis: OpenflexureAxis, value: i64) {
+        match axis {
+            OpenflexureAxis::X => self.target.x = value,
+            OpenflexureAxis::Y => self.target.y = value,
+            OpenflexureAxis::Z => self.target.z = value,
+        }
+    }
+
+    /// Entry of the target of `axis`, in µm if the axis is shown in µm or mm.
+    /// Targets outside of the soft limits are not accepted.
+    fn entry<D>(axis: OpenflexureAxis, target: i64, ctx: &Context<D>) -> NumberEntry {
+        let (min, max) = match ctx.config.limits.axis(axis) {
+            Some(limits) => (limits.min, limits.max),
+            None => (-MAX_TARGET, MAX_TARGET),
+        };
+        let units = &ctx.config.units;
+        match units.to_um(axis, target) {
+            Some(um) => {
+                let min = units.to_um(axis, min).unwrap_or_default().ceil() as i64;
+                let max = units.to_um(axis, max).unwrap_or_default().floor() as i64;
+                // limits closer than a µm
+                NumberEntry::new(um.round() as i64, min, max.max(min))
+            }
+            None => NumberEntry::new(target, min, max),
+        }
+    }
+
+    /// Handles the input while a target is entered, it is taken once it is
+    /// complete and within the range.
+    fn handle_entry<D>(&mut self, event: InputEvent, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let Some((axis, entry)) = &mut self.editing else {
+            return;
+        };
+        match entry.handle(event) {
+            EntryOutcome::Editing => return,
+            EntryOutcome::Done if !entry.in_range() => {
+                ctx.toast(ctx.tr("Out of range"));
+                return;
+            }
+            EntryOutcome::Done => {
+                let (axis, value) = (*axis, entry.value());
+                self.set_target(axis, ctx.config.units.um_to_steps(axis, value));
+            }
+            EntryOutcome::Cancelled => {}
+        }
+        self.editing = None;
+        self.regions.invalidate();
+    }
+}
+
+impl<D> Screen<D> for GotoScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "goto"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        if self.editing.is_some() {
+            self.handle_entry(event, ctx);
+            return Transition::Stay;
+        }
+
+        match event {
+            InputEvent::Up => {
+                self.selection_idx = step_selection(self.selection_idx, FIELDS.len(), true);
+            }
+            InputEvent::Down => {
+                self.selection_idx = step_selection(self.selection_idx, FIELDS.len(), false);
+            }
+            InputEvent::Select => match FIELDS[self.selection_idx as usize] {
+                Field::Axis(axis) => {
+                    self.editing = Some((axis, Self::entry(axis, self.target.axis(axis), ctx)));
+                    self.regions.invalidate();
+                }
+                Field::Move => ctx.request(Request::MoveTo {
+                    x: self.target.x,
+                    y: self.target.y,
+                    z: self.target.z,
+                }),
+                Field::Back => return Transition::Pop,
+            },
+            InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        let area = content_area(ctx);
+        let invalidated = self.regions.take_invalidated();
+        if invalidated {
+            draw_frame(ctx)?;
+        }
+
+        if let Some((axis, entry)) = &self.editing {
+            if invalidated {
+                let title = ctx.tr(target_title(*axis));
+                if ctx.config.units.is_physical(*axis) {
+                    draw_title(ctx, &format!("{} µm", title))?;
+                } else {
+                    draw_title(ctx, title)?;
+                }
+            }
+            let row = Rectangle::with_center(
+                area.center(),
+                Size::new(area.size.width, row_height(&ctx.theme)),
+            );
+            self.regions
+                .draw(entry, row, &ctx.theme, &mut ctx.display)
+                .map_err(display_error)?;
+            return ctx.display.flush().map_err(display_error);
+        }
+
+        let rows: Vec<_> = FIELDS
+            .iter()
+            .map(|field| {
+                let value = match field {
+                    Field::Axis(axis) => ctx
+                        .config
+                        .units
+                        .format_with_unit(*axis, self.target.axis(*axis)),
+                    Field::Move => ">".to_string(),
+                    Field::Back => "<".to_string(),
+                };
+                ValueRow::new(ctx.tr(field.name()), value)
+            })
+            .collect();
+        let footer = if self.busy { ctx.tr("Moving") } else { "" };
+
+        List::new(&rows, self.selection_idx)
+            .with_footer(footer, ctx.theme.accent)
+            .draw_changed(area, &ctx.theme, &mut ctx.display, &mut self.regions)
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+
+    /// Redraws when a move started or finished.
+    fn tick(&mut self, ctx: &mut Context<D>) -> bool {
+        let busy = ctx.is_busy();
+        let changed = busy != self.busy;
+        self.busy = busy;
+        changed
+    }
+}
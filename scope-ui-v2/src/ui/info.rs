@@ -0,0 +1,229 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+use super::{
+    Context, Screen, Transition, content_area, display_error, draw_frame, step_selection,
+    widgets::{List, Regions, ValueRow, Widget},
+};
+use crate::{
+    diagnostics::{self, GIT_HASH, SystemInfo, VERSION},
+    display::Panel,
+    input::InputEvent,
+};
+
+/// Time after which the information is read again while the screen is shown
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether a server answered the last ping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Reachability {
+    /// No ping finished yet
+    #[default]
+    Checking,
+    Online(Duration),
+    Offline,
+}
+
+impl Reachability {
+    fn from_ping(result: anyhow::Result<Duration>) -> Self {
+        match result {
+            Ok(latency) => Self::Online(latency),
+            Err(_) => Self::Offline,
+        }
+    }
+
+    fn text<D>(self, ctx: &Context<D>) -> String
+    where
+        D: Panel,
+    {
+        match self {
+            Self::Checking => "...".to_string(),
+            Self::Online(latency) => format!("{} ms", latency.as_millis()),
+            Self::Offline => ctx.tr("offline").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Servers {
+    openflexure: Reachability,
+    phoenix: Reachability,
+}
+
+/// Version, network and health of the device and whether the servers can be
+/// reached.
+pub struct InfoScreen {
+    system: SystemInfo,
+    /// Rows read from the system at the last refresh
+    rows: Vec<(&'static str, String)>,
+    servers: watch::Receiver<Servers>,
+    servers_tx: watch::Sender<Servers>,
+    refreshed: Option<Instant>,
+    selection_idx: u32,
+    regions: Regions,
+}
+
+impl InfoScreen {
+    pub fn new(system: SystemInfo) -> Self {
+        let (servers_tx, servers) = watch::channel(Servers::default());
+        Self {
+            system,
+            rows: Vec::new(),
+            servers,
+            servers_tx,
+            refreshed: None,
+            selection_idx: 0,
+            regions: Regions::new(),
+        }
+    }
+
+    /// Reads the system information again and pings the servers in the
+    /// background.
+    fn refresh<D>(&mut self, ctx: &Context<D>)
+    where
+        D: Panel,
+    {
+        self.refreshed = Some(Instant::now());
+        self.rows = self.read_system(ctx);
+
+        let client = ctx.client().clone();
+        let servers_tx = self.servers_tx.clone();
+        tokio::spawn(async move {
+            let (openflexure, phoenix) =
+                tokio::join!(client.ping_openflexure(), client.ping_phoenix());
+            // the screen may be closed in the meantime
+            let _ = servers_tx.send(Servers {
+                openflexure: Reachability::from_ping(openflexure),
+                phoenix: Reachability::from_ping(phoenix),
+            });
+        });
+    }
+
+    fn read_system<D>(&self, ctx: &Context<D>) -> Vec<(&'static str, String)>
+    where
+        D: Panel,
+    {
+        let unknown = || "-".to_string();
+        let mut rows = vec![
+            ("Version", VERSION.to_string()),
+            ("Git", GIT_HASH.to_string()),
+            ("Host", self.system.hostname().unwrap_or_else(unknown)),
+        ];
+
+        let addresses = self.system.ip_addresses();
+        if addresses.is_empty() {
+            rows.push(("IP", unknown()));
+        }
+        rows.extend(addresses.iter().map(|address| ("IP", address.to_string())));
+
+        // only the serial device is checked, not whether the server talks to
+        // the board
+        let sangaboard = if self.system.device_exists(&ctx.config.sangaboard_device) {
+            "present"
+        } else {
+            "missing"
+        };
+        rows.extend([
+            ("Board device", ctx.tr(sangaboard).to_string()),
+            (
+                "Uptime",
+                self.system
+                    .uptime()
+                    .map_or_else(unknown, diagnostics::format_duration),
+            ),
+            (
+                "CPU temp",
+                self.system
+                    .cpu_temperature()
+                    .map_or_else(unknown, |temp| format!("{:.1} °C", temp)),
+            ),
+            (
+                "SD free",
+                self.system
+                    .disk_free()
+                    .map_or_else(unknown, diagnostics::format_bytes),
+            ),
+        ]);
+        rows
+    }
+
+    /// Rows shown on the screen, the servers are shown last.
+    fn value_rows<D>(&self, servers: Servers, ctx: &Context<D>) -> Vec<ValueRow<'static>>
+    where
+        D: Panel,
+    {
+        let server_rows = [
+            ("OpenFlexure", servers.openflexure.text(ctx)),
+            ("Phoenix", servers.phoenix.text(ctx)),
+        ];
+        self.rows
+            .iter()
+            .cloned()
+            .chain(server_rows)
+            .map(|(label, value)| ValueRow::new(ctx.tr(label), value))
+            .collect()
+    }
+}
+
+impl<D> Screen<D> for InfoScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "info"
+    }
+
+    fn handle(&mut self, event: InputEvent, _ctx: &mut Context<D>) -> Transition<D> {
+        // the two server rows follow the system rows
+        let len = self.rows.len() + 2;
+        match event {
+            InputEvent::Up => {
+                self.selection_idx = step_selection(self.selection_idx, len, true);
+            }
+            InputEvent::Down => {
+                self.selection_idx = step_selection(self.selection_idx, len, false);
+            }
+            InputEvent::Select | InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        if self.regions.take_invalidated() {
+            draw_frame(ctx)?;
+        }
+        let servers = *self.servers.borrow_and_update();
+        let rows = self.value_rows(servers, ctx);
+
+        List::new(&rows, self.selection_idx)
+            .draw_changed(
+                content_area(ctx),
+                &ctx.theme,
+                &mut ctx.display,
+                &mut self.regions,
+            )
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, ctx: &mut Context<D>) {
+        self.regions.invalidate();
+        self.refresh(ctx);
+    }
+
+    fn tick(&mut self, ctx: &mut Context<D>) -> bool {
+        if self
+            .refreshed
+            .is_none_or(|refreshed| refreshed.elapsed() >= REFRESH_INTERVAL)
+        {
+            self.refresh(ctx);
+            return true;
+        }
+        self.servers.has_changed().unwrap_or(false)
+    }
+}
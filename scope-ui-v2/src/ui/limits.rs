@@ -0,0 +1,299 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use super::{
+    Context, Screen, Transition, content_area, display_error, draw_frame, draw_title,
+    step_selection,
+    widgets::{ConfirmScreen, List, Regions, ValueRow, Widget, row_height},
+};
+use crate::{
+    client::OpenflexureAxis,
+    display::Panel,
+    input::InputEvent,
+    limits::{AxisLimits, LimitMode, SoftLimits},
+    request::Request,
+};
+
+/// Rows of the limits screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Mode,
+    Axis(OpenflexureAxis),
+    Clear,
+    Back,
+}
+
+const ROWS: [Row; 6] = [
+    Row::Mode,
+    Row::Axis(OpenflexureAxis::X),
+    Row::Axis(OpenflexureAxis::Y),
+    Row::Axis(OpenflexureAxis::Z),
+    Row::Clear,
+    Row::Back,
+];
+
+impl Row {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Mode => "Mode",
+            Self::Axis(OpenflexureAxis::X) => "X Axis",
+            Self::Axis(OpenflexureAxis::Y) => "Y Axis",
+            Self::Axis(OpenflexureAxis::Z) => "Z Axis",
+            Self::Clear => "Clear",
+            Self::Back => "Back",
+        }
+    }
+}
+
+fn mode_label(mode: LimitMode) -> &'static str {
+    match mode {
+        LimitMode::Off => "Off",
+        LimitMode::Clamp => "Clamp",
+        LimitMode::Reject => "Reject",
+    }
+}
+
+/// Recording the limits of an axis by jogging it to its physical ends.
+struct Calibration {
+    axis: OpenflexureAxis,
+    /// End recorded first, the other one is recorded next
+    first: Option<i64>,
+    /// Limits before the calibration, restored when it is cancelled
+    previous: SoftLimits,
+}
+
+/// Software travel limits of the stage. Selecting an axis calibrates it: the
+/// encoder moves the axis to one end and then the other, Select records
+/// each end.
+pub struct LimitsScreen {
+    selection_idx: u32,
+    calibrating: Option<Calibration>,
+    regions: Regions,
+}
+
+impl LimitsScreen {
+    pub fn new() -> Self {
+        Self {
+            selection_idx: 0,
+            calibrating: None,
+            regions: Regions::new(),
+        }
+    }
+
+    fn start_calibration<D>(&mut self, axis: OpenflexureAxis, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let previous = ctx.config.limits.clone();
+        // the old limits would stop the axis before its ends
+        ctx.config.limits.set_axis(axis, None);
+        ctx.apply_limits();
+        self.calibrating = Some(Calibration {
+            axis,
+            first: None,
+            previous,
+        });
+        self.regions.invalidate();
+    }
+
+    /// Handles the input while an axis is calibrated. The encoder moves the
+    /// axis, Select records an end and a long press cancels.
+    fn handle_calibration<D>(&mut self, event: InputEvent, ctx: &mut Context<D>)
+    where
+        D: Panel,
+    {
+        let Some(calibration) = &mut self.calibrating else {
+            return;
+        };
+        let axis = calibration.axis;
+        let config = &ctx.config;
+        let distance = match event {
+            InputEvent::Up => config.step_distance(axis, false),
+            InputEvent::Down => -config.step_distance(axis, false),
+            InputEvent::FineUp => config.step_distance(axis, true),
+            InputEvent::FineDown => -config.step_distance(axis, true),
+            InputEvent::Select if ctx.is_busy() => {
+                // the position is only known after the move
+                ctx.toast(ctx.tr("Moving"));
+                return;
+            }
+            InputEvent::Select => {
+                let position = ctx.position.axis(axis);
+                match calibration.first {
+                    None => calibration.first = Some(position),
+                    Some(first) => {
+                        let limits = AxisLimits::between(first, position);
+                        ctx.config.limits.set_axis(axis, Some(limits));
+                        ctx.apply_limits();
+                        ctx.save_config();
+                        ctx.toast(ctx.tr("Saved"));
+                        self.calibrating = None;
+                    }
+                }
+                self.regions.invalidate();
+                return;
+            }
+            InputEvent::LongPress => {
+                ctx.config.limits = calibration.previous.clone();
+                ctx.apply_limits();
+                self.calibrating = None;
+                self.regions.invalidate();
+                return;
+            }
+            _ => return,
+        };
+        ctx.request(Request::MoveAxis { axis, distance });
+    }
+
+    fn value_text<D>(row: Row, ctx: &Context<D>) -> String
+    where
+        D: Panel,
+    {
+        let limits = &ctx.config.limits;
+        match row {
+            Row::Mode => ctx.tr(mode_label(limits.mode)).to_string(),
+            Row::Axis(axis) => match limits.configured(axis) {
+                Some(limits) => {
+                    let units = &ctx.config.units;
+                    format!(
+                        "{}..{}",
+                        units.format(axis, limits.min).0,
+                        units.format_with_unit(axis, limits.max)
+                    )
+                }
+                None => "-".to_string(),
+            },
+            Row::Clear => ">".to_string(),
+            Row::Back => "<".to_string(),
+        }
+    }
+
+    fn render_calibration<D>(
+        &mut self,
+        invalidated: bool,
+        ctx: &mut Context<D>,
+    ) -> anyhow::Result<()>
+    where
+        D: Panel,
+    {
+        let Some(calibration) = &self.calibrating else {
+            return Ok(());
+        };
+        let axis = Row::Axis(calibration.axis);
+        if invalidated {
+            let end = if calibration.first.is_none() {
+                "Jog to 1st end"
+            } else {
+                "Jog to 2nd end"
+            };
+            draw_title(ctx, ctx.tr(end))?;
+        }
+
+        let area = content_area(ctx);
+        let row = Rectangle::with_center(
+            area.center(),
+            Size::new(area.size.width, row_height(&ctx.theme)),
+        );
+        let value = ctx
+            .config
+            .units
+            .format_with_unit(calibration.axis, ctx.position.axis(calibration.axis));
+        let position = ValueRow::new(ctx.tr(axis.name()), value);
+        self.regions
+            .draw(&position, row, &ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+}
+
+impl Default for LimitsScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Screen<D> for LimitsScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "limits"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        if self.calibrating.is_some() {
+            self.handle_calibration(event, ctx);
+            return Transition::Stay;
+        }
+
+        match event {
+            InputEvent::Up => {
+                self.selection_idx = step_selection(self.selection_idx, ROWS.len(), true);
+            }
+            InputEvent::Down => {
+                self.selection_idx = step_selection(self.selection_idx, ROWS.len(), false);
+            }
+            InputEvent::Select => match ROWS[self.selection_idx as usize] {
+                Row::Mode => {
+                    ctx.config.limits.mode = ctx.config.limits.mode.next();
+                    ctx.apply_limits();
+                    ctx.save_config();
+                }
+                Row::Axis(axis) => self.start_calibration(axis, ctx),
+                Row::Clear => {
+                    return Transition::Push(Box::new(ConfirmScreen::new(
+                        ctx.tr("Clear"),
+                        ctx.tr("Clear all limits?"),
+                        |ctx: &mut Context<D>| {
+                            ctx.config.limits = SoftLimits {
+                                mode: ctx.config.limits.mode,
+                                ..SoftLimits::default()
+                            };
+                            ctx.apply_limits();
+                            ctx.save_config();
+                        },
+                    )));
+                }
+                Row::Back => return Transition::Pop,
+            },
+            InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        let invalidated = self.regions.take_invalidated();
+        if invalidated {
+            draw_frame(ctx)?;
+        }
+        if self.calibrating.is_some() {
+            return self.render_calibration(invalidated, ctx);
+        }
+
+        let texts: Vec<_> = ROWS
+            .iter()
+            .map(|row| (ctx.tr(row.name()), Self::value_text(*row, ctx)))
+            .collect();
+        let rows: Vec<_> = texts
+            .iter()
+            .map(|(label, value)| ValueRow::new(label, value))
+            .collect();
+
+        List::new(&rows, self.selection_idx)
+            .draw_changed(
+                content_area(ctx),
+                &ctx.theme,
+                &mut ctx.display,
+                &mut self.regions,
+            )
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+}
@@ -0,0 +1,145 @@
+use log::error;
+
+use super::{Context, Screen, Transition, display_error};
+use crate::{
+    console::LogConsole,
+    display::{Panel, ili9341::Orientation},
+    input::InputEvent,
+    logging::LogBuffer,
+};
+
+/// Log records streamed to the display, drawing only the new lines.
+///
+/// Panels like the ILI9341 only scroll in hardware along their long side,
+/// so a landscape panel is turned to portrait while the log is shown, to
+/// scroll the lines below the fixed header. Panels which can not scroll at
+/// all keep their orientation and redraw the lines instead.
+pub struct LogScreen {
+    console: LogConsole,
+}
+
+impl LogScreen {
+    pub fn new(log: LogBuffer) -> Self {
+        Self {
+            console: LogConsole::new(log),
+        }
+    }
+}
+
+/// Orientation the log is shown in, a quarter turn from `orientation` if the
+/// panel only scrolls in portrait.
+fn console_orientation(display: &impl Panel, orientation: Orientation) -> Orientation {
+    let portrait = match orientation {
+        Orientation::Landscape => Orientation::Portrait,
+        Orientation::LandscapeFlipped => Orientation::PortraitFlipped,
+        portrait => portrait,
+    };
+    if !display.can_scroll(orientation) && display.can_scroll(portrait) {
+        portrait
+    } else {
+        orientation
+    }
+}
+
+impl<D> Screen<D> for LogScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn handle(&mut self, event: InputEvent, _ctx: &mut Context<D>) -> Transition<D> {
+        match event {
+            InputEvent::Select | InputEvent::LongPress => Transition::Pop,
+            _ => Transition::Stay,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        self.console
+            .update(&mut ctx.display, &ctx.theme)
+            .map_err(display_error)
+    }
+
+    fn on_enter(&mut self, ctx: &mut Context<D>) {
+        let orientation = console_orientation(&ctx.display, ctx.config.orientation);
+        if orientation != ctx.config.orientation
+            && let Err(e) = ctx.display.set_orientation(orientation)
+        {
+            error!("failed to rotate display for the log: {:?}", e);
+        }
+        if let Err(e) = self.console.enter(&mut ctx.display, &ctx.theme) {
+            error!("failed to draw log console: {:?}", e);
+        }
+    }
+
+    fn on_exit(&mut self, ctx: &mut Context<D>) {
+        self.console.exit(&mut ctx.display);
+        let orientation = ctx.config.orientation;
+        if console_orientation(&ctx.display, orientation) != orientation
+            && let Err(e) = ctx.display.set_orientation(orientation)
+        {
+            error!("failed to rotate display back: {:?}", e);
+        }
+    }
+
+    fn tick(&mut self, _ctx: &mut Context<D>) -> bool {
+        // new lines are only looked up when drawing
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use embedded_graphics::prelude::*;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{config::AppConfig, display::simulated::SimulatedPanel, theme::Theme};
+
+    fn context(orientation: Orientation, log: &LogBuffer) -> Context<SimulatedPanel> {
+        let (events, _) = mpsc::unbounded_channel();
+        let config = AppConfig {
+            orientation,
+            ..AppConfig::default()
+        };
+        Context::new(
+            SimulatedPanel::new(Size::new(320, 240), orientation),
+            config,
+            PathBuf::new(),
+            Theme::color(),
+            log.clone(),
+            events,
+        )
+    }
+
+    #[test]
+    fn test_landscape_panel_scrolls_in_portrait() {
+        let log = LogBuffer::new(10);
+        let mut ctx = context(Orientation::LandscapeFlipped, &log);
+        let mut screen = LogScreen::new(log);
+
+        screen.on_enter(&mut ctx);
+        assert_eq!(ctx.display.size(), Size::new(240, 320));
+        assert!(screen.console.scrolls_in_hardware());
+
+        screen.on_exit(&mut ctx);
+        assert_eq!(ctx.display.size(), Size::new(320, 240));
+    }
+
+    #[test]
+    fn test_portrait_panel_keeps_orientation() {
+        let log = LogBuffer::new(10);
+        let mut ctx = context(Orientation::Portrait, &log);
+        let mut screen = LogScreen::new(log);
+
+        screen.on_enter(&mut ctx);
+        assert_eq!(ctx.display.size(), Size::new(240, 320));
+        assert!(screen.console.scrolls_in_hardware());
+        screen.on_exit(&mut ctx);
+        assert_eq!(ctx.display.size(), Size::new(240, 320));
+    }
+}
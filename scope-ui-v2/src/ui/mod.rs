@@ -0,0 +1,608 @@
+//! Screens of the UI and the navigation between them.
+//!
+//! Every screen implements [`Screen`] and only sees the input events while it
+//! is on top of the [`ScreenStack`]. Screens open other screens by returning a
+//! [`Transition`], so new screens can be added without touching the others.
+
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use log::{debug, error, info, warn};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    client::{AppClient, OpenFlexurePosition},
+    config::{AppConfig, Bookmark},
+    display::Panel,
+    history::PositionHistory,
+    input::InputEvent,
+    limits::LimitError,
+    logging::LogBuffer,
+    request::{Request, RequestQueue},
+    theme::Theme,
+};
+
+mod app;
+pub mod bookmarks;
+pub mod calibration;
+pub mod control;
+pub mod edit;
+pub mod goto;
+pub mod info;
+pub mod limits;
+pub mod log_view;
+pub mod settings;
+pub mod widgets;
+
+pub use app::{App, AppEvent};
+
+/// State shared by all screens.
+pub struct Context<D> {
+    pub display: D,
+    pub config: AppConfig,
+    pub config_path: PathBuf,
+    pub theme: Theme,
+    pub log: LogBuffer,
+    /// Stage position read last
+    pub position: OpenFlexurePosition,
+    /// Positions after the last moves, to undo them
+    history: PositionHistory,
+    client: AppClient,
+    /// Sender of the event loop, used by the tasks running the requests
+    events: UnboundedSender<AppEvent>,
+    requests: RequestQueue,
+    /// Message shown over the screen and when it is hidden
+    toast: Option<(String, Instant)>,
+}
+
+/// Time a toast is shown
+const TOAST_DURATION: Duration = Duration::from_secs(2);
+
+impl<D> Context<D>
+where
+    D: Panel,
+{
+    pub fn new(
+        display: D,
+        config: AppConfig,
+        config_path: PathBuf,
+        theme: Theme,
+        log: LogBuffer,
+        events: UnboundedSender<AppEvent>,
+    ) -> Self {
+        Self {
+            client: AppClient::new(&config),
+            display,
+            config,
+            config_path,
+            theme,
+            log,
+            position: OpenFlexurePosition::default(),
+            history: PositionHistory::new(),
+            events,
+            requests: RequestQueue::new(),
+            toast: None,
+        }
+    }
+
+    /// Queues a request to the servers, it runs as task so the UI keeps
+    /// responding.
+    pub fn request(&mut self, request: Request) {
+        self.requests.push(request);
+        self.start_request();
+    }
+
+    /// Captures an image at every bookmark, in the order of the list.
+    /// Stopping drops the positions which were not captured yet.
+    pub fn start_batch(&mut self) {
+        if self.config.bookmarks.is_empty() {
+            self.toast(self.tr("No bookmarks"));
+            return;
+        }
+        let positions: Vec<_> = self
+            .config
+            .bookmarks
+            .iter()
+            .map(Bookmark::position)
+            .collect();
+        info!("start batch of {} positions", positions.len());
+        let queued = positions
+            .into_iter()
+            .take_while(|position| {
+                self.requests.push(Request::CaptureAt {
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                })
+            })
+            .count();
+        self.start_request();
+        if queued < self.config.bookmarks.len() {
+            self.toast(self.tr("Batch too long"));
+        }
+    }
+
+    fn start_request(&mut self) {
+        let Some(request) = self.requests.start_next() else {
+            return;
+        };
+        let client = self.client.clone();
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            // a panicking request has to finish as well, or the queue stays
+            // busy
+            let result = match tokio::spawn(request.clone().execute(client)).await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!("Request task failed: {}", e)),
+            };
+            // the event loop only stops when the app shuts down
+            let _ = events.send(AppEvent::Completed { request, result });
+        });
+    }
+
+    /// Drops the queued requests, so the stage stops after the running move.
+    pub fn stop(&mut self) {
+        let dropped = self.requests.clear();
+        info!("stop, dropped {} queued requests", dropped);
+        self.toast(self.tr("Stopped"));
+    }
+
+    /// Stops the stage at once: drops the queued requests like [`Self::stop`]
+    /// and cancels the running OpenFlexure actions instead of waiting for
+    /// them to finish.
+    pub fn emergency_stop(&mut self) {
+        let dropped = self.requests.clear();
+        warn!("emergency stop, dropped {} queued requests", dropped);
+        let client = self.client.clone();
+        // the queue waits for the running request, which is cancelled here
+        tokio::spawn(async move {
+            match client.cancel_actions().await {
+                Ok(cancelled) => info!("cancelled {} actions", cancelled),
+                Err(e) => error!("failed to cancel actions {:?}", e),
+            }
+        });
+        self.toast(self.tr("Emergency stop"));
+    }
+
+    /// Handles the result of the running request and starts the next one.
+    /// Returns whether the screen has to be redrawn.
+    fn completed(
+        &mut self,
+        request: Request,
+        result: anyhow::Result<Option<OpenFlexurePosition>>,
+    ) -> bool {
+        self.requests.finish();
+        self.start_request();
+        match result {
+            Ok(Some(position)) => {
+                let before = std::mem::replace(&mut self.position, position);
+                if request.is_move() {
+                    let now = Instant::now();
+                    // the first move can be undone back to the position read
+                    // before it
+                    if self.history.is_empty() {
+                        self.history.record(before, false, now);
+                    }
+                    // steps of the encoder and the joystick are undone together
+                    let burst = matches!(request, Request::MoveAxis { .. } | Request::Jog { .. });
+                    self.history.record(position, burst, now);
+                }
+                if let Request::MoveTo { .. } = request {
+                    self.toast(self.tr("Position reached"));
+                }
+                true
+            }
+            Ok(None) => false,
+            Err(e) if e.is::<LimitError>() => {
+                warn!("did not {}: {}", request.name(), e);
+                self.toast(self.tr("Outside of limits"));
+                true
+            }
+            Err(e) => {
+                error!("failed to {} {:?}", request.name(), e);
+                self.toast(format!("Failed to {}", request.name()));
+                true
+            }
+        }
+    }
+
+    /// Shows a short message over the screen.
+    pub fn toast(&mut self, message: impl Into<String>) {
+        self.toast = Some((message.into(), Instant::now() + TOAST_DURATION));
+    }
+
+    /// Moves the stage back to the position before the last move.
+    pub fn undo(&mut self) {
+        // the running move is not in the history yet
+        if self.is_busy() {
+            self.toast(self.tr("Moving"));
+            return;
+        }
+        match self.history.undo() {
+            Some(position) => {
+                info!("undo move, back to {:?}", position);
+                self.request(Request::MoveTo {
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                });
+            }
+            None => self.toast(self.tr("No previous position")),
+        }
+    }
+
+    /// Number of moves which can be undone
+    pub fn undo_steps(&self) -> usize {
+        self.history.undo_steps()
+    }
+
+    /// Whether a request is running, e.g. to show that the stage moves.
+    pub fn is_busy(&self) -> bool {
+        self.requests.is_busy()
+    }
+
+    /// Client of the configured servers, for requests which do not go through
+    /// the request queue like pings.
+    pub fn client(&self) -> &AppClient {
+        &self.client
+    }
+
+    /// Translates an English text of the UI into the configured language.
+    pub fn tr(&self, text: &'static str) -> &'static str {
+        self.config.language.translate(text)
+    }
+
+    /// Connects to the servers in the config, after they were changed, and
+    /// reads the stage position from the new server.
+    pub fn reconnect(&mut self) {
+        info!(
+            "switch to servers {} and {}",
+            self.config.openflexure_url, self.config.phoenix_url
+        );
+        self.client = AppClient::new(&self.config);
+        // the positions belong to the stage of the old server
+        self.history = PositionHistory::new();
+        self.request(Request::RefreshPosition);
+    }
+
+    /// Checks the following moves against the soft limits in the config,
+    /// after they were changed.
+    pub fn apply_limits(&mut self) {
+        info!("apply soft limits {:?}", self.config.limits);
+        self.client = AppClient::new(&self.config);
+    }
+
+    /// Stores the config in the config file, after a setting was changed.
+    pub fn save_config(&self) {
+        match self.config.save(&self.config_path) {
+            Ok(()) => info!("saved settings to {}", self.config_path.display()),
+            Err(e) => error!("failed to save settings: {:?}", e),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.display.clear(self.theme.background).unwrap();
+    }
+}
+
+/// Navigation requested by a screen after handling an event.
+pub enum Transition<D> {
+    Stay,
+    /// Opens the screen on top of the current one
+    Push(Box<dyn Screen<D>>),
+    /// Goes back to the previous screen
+    Pop,
+}
+
+/// Screen of the UI, e.g. the control menu or the settings.
+pub trait Screen<D> {
+    /// Name used in the debug log
+    fn name(&self) -> &'static str;
+
+    /// Handles an input event while the screen is shown.
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D>;
+
+    /// Draws the screen.
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()>;
+
+    /// Called when the screen is shown, after it was opened, uncovered or
+    /// the display woke up.
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {}
+
+    /// Called when the screen is closed or covered by another screen.
+    fn on_exit(&mut self, _ctx: &mut Context<D>) {}
+
+    /// Called periodically, returns whether the screen has to be redrawn.
+    fn tick(&mut self, _ctx: &mut Context<D>) -> bool {
+        false
+    }
+}
+
+/// Opened screens, the top screen is shown. The first screen is never closed.
+pub struct ScreenStack<D> {
+    screens: Vec<Box<dyn Screen<D>>>,
+}
+
+impl<D> ScreenStack<D>
+where
+    D: Panel,
+{
+    pub fn new(root: Box<dyn Screen<D>>) -> Self {
+        Self {
+            screens: vec![root],
+        }
+    }
+
+    pub fn top(&mut self) -> &mut dyn Screen<D> {
+        self.screens.last_mut().unwrap().as_mut()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.screens.len()
+    }
+
+    /// Applies the transition of the top screen, returns whether the shown
+    /// screen changed.
+    pub fn apply(&mut self, transition: Transition<D>, ctx: &mut Context<D>) -> bool {
+        match transition {
+            Transition::Stay => return false,
+            Transition::Push(screen) => {
+                self.top().on_exit(ctx);
+                self.screens.push(screen);
+            }
+            Transition::Pop if self.screens.len() == 1 => return false,
+            Transition::Pop => {
+                self.top().on_exit(ctx);
+                self.screens.pop();
+            }
+        }
+        debug!("switch to screen {}", self.top().name());
+        self.top().on_enter(ctx);
+        true
+    }
+
+    /// Closes all screens, calling [`Screen::on_exit`] of the shown screen.
+    pub fn exit(&mut self, ctx: &mut Context<D>) {
+        self.top().on_exit(ctx);
+        self.screens.truncate(1);
+    }
+}
+
+/// Moves a list selection by one entry, wrapping around at both ends.
+pub fn step_selection(idx: u32, len: usize, up: bool) -> u32 {
+    let len = len as u32;
+    if up {
+        (idx + 1) % len
+    } else {
+        (idx + len - 1) % len
+    }
+}
+
+/// Clears the display and draws the border around the content.
+pub fn draw_frame<D>(ctx: &mut Context<D>) -> anyhow::Result<()>
+where
+    D: Panel,
+{
+    ctx.clear();
+    let border_style = PrimitiveStyle::with_stroke(ctx.theme.text, ctx.theme.border_width);
+    ctx.display
+        .bounding_box()
+        .into_styled(border_style)
+        .draw(&mut ctx.display)
+        .map_err(display_error)?;
+    Ok(())
+}
+
+/// Draws a centered title at the top of the content area.
+pub fn draw_title<D>(ctx: &mut Context<D>, title: &str) -> anyhow::Result<()>
+where
+    D: Panel,
+{
+    let area = content_area(ctx);
+    Text::with_text_style(
+        title,
+        Point::new(area.center().x, area.top_left.y),
+        MonoTextStyle::new(ctx.theme.font, ctx.theme.accent),
+        TextStyleBuilder::new()
+            .baseline(Baseline::Top)
+            .alignment(Alignment::Center)
+            .build(),
+    )
+    .draw(&mut ctx.display)
+    .map_err(display_error)?;
+    Ok(())
+}
+
+/// Area inside the border drawn by [`draw_frame`].
+pub fn content_area<D>(ctx: &Context<D>) -> Rectangle
+where
+    D: Panel,
+{
+    ctx.display
+        .bounding_box()
+        .offset(-((ctx.theme.border_width + ctx.theme.margin) as i32))
+}
+
+pub fn display_error<E: Debug>(e: E) -> anyhow::Error {
+    anyhow::anyhow!("failed to draw to display: {:?}", e)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use embedded_graphics::geometry::Size;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::display::{ili9341::Orientation, simulated::SimulatedPanel};
+
+    /// Screen recording its calls, opening another screen on select
+    struct Probe {
+        name: &'static str,
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Probe {
+        fn new(name: &'static str, calls: &Rc<RefCell<Vec<String>>>) -> Box<Self> {
+            Box::new(Self {
+                name,
+                calls: calls.clone(),
+            })
+        }
+
+        fn record(&self, call: &str) {
+            self.calls
+                .borrow_mut()
+                .push(format!("{} {}", self.name, call));
+        }
+    }
+
+    impl Screen<SimulatedPanel> for Probe {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn handle(
+            &mut self,
+            event: InputEvent,
+            _ctx: &mut Context<SimulatedPanel>,
+        ) -> Transition<SimulatedPanel> {
+            match event {
+                InputEvent::Select => Transition::Push(Probe::new("child", &self.calls)),
+                InputEvent::LongPress => Transition::Pop,
+                _ => Transition::Stay,
+            }
+        }
+
+        fn render(&mut self, _ctx: &mut Context<SimulatedPanel>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn on_enter(&mut self, _ctx: &mut Context<SimulatedPanel>) {
+            self.record("enter");
+        }
+
+        fn on_exit(&mut self, _ctx: &mut Context<SimulatedPanel>) {
+            self.record("exit");
+        }
+    }
+
+    #[test]
+    fn test_screen_stack() {
+        let (events, _) = mpsc::unbounded_channel();
+        let display = SimulatedPanel::new(Size::new(320, 240), Orientation::Landscape);
+        let mut ctx = Context::new(
+            display,
+            AppConfig::default(),
+            PathBuf::new(),
+            Theme::color(),
+            LogBuffer::default(),
+            events,
+        );
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut stack = ScreenStack::new(Probe::new("root", &calls));
+
+        let mut send = |stack: &mut ScreenStack<_>, event| {
+            let transition = stack.top().handle(event, &mut ctx);
+            stack.apply(transition, &mut ctx)
+        };
+        assert!(send(&mut stack, InputEvent::Select));
+        assert_eq!(stack.depth(), 2);
+        assert!(!send(&mut stack, InputEvent::Up));
+        assert!(send(&mut stack, InputEvent::LongPress));
+        // the first screen stays open
+        assert!(!send(&mut stack, InputEvent::LongPress));
+        assert_eq!(stack.depth(), 1);
+
+        assert_eq!(
+            *calls.borrow(),
+            ["root exit", "child enter", "child exit", "root enter"]
+        );
+    }
+
+    /// Context whose requests fail at once, as nothing listens at the server
+    /// URL
+    fn offline_context() -> (Context<SimulatedPanel>, mpsc::UnboundedReceiver<AppEvent>) {
+        let (events, completed) = mpsc::unbounded_channel();
+        let display = SimulatedPanel::new(Size::new(320, 240), Orientation::Landscape);
+        let config = AppConfig {
+            openflexure_url: "http://127.0.0.1:1".try_into().unwrap(),
+            ..AppConfig::default()
+        };
+        let ctx = Context::new(
+            display,
+            config,
+            PathBuf::new(),
+            Theme::color(),
+            LogBuffer::default(),
+            events,
+        );
+        (ctx, completed)
+    }
+
+    #[tokio::test]
+    async fn test_undo_moves() {
+        let (mut ctx, mut completed) = offline_context();
+        let position = |x| OpenFlexurePosition { x, y: 0, z: 0 };
+
+        ctx.completed(Request::RefreshPosition, Ok(Some(position(100))));
+        assert_eq!(ctx.undo_steps(), 0);
+        let step = Request::MoveAxis {
+            axis: crate::client::OpenflexureAxis::X,
+            distance: 100,
+        };
+        ctx.completed(step.clone(), Ok(Some(position(200))));
+        ctx.completed(step, Ok(Some(position(300))));
+        // refreshing does not add a position
+        ctx.completed(Request::RefreshPosition, Ok(Some(position(300))));
+        ctx.completed(Request::MoveTo { x: 0, y: 0, z: 0 }, Ok(Some(position(0))));
+        assert_eq!(ctx.undo_steps(), 2);
+
+        ctx.undo();
+        assert!(ctx.is_busy());
+        let Some(AppEvent::Completed { request, result }) = completed.recv().await else {
+            panic!("request did not complete");
+        };
+        assert_eq!(request, Request::MoveTo { x: 300, y: 0, z: 0 });
+        ctx.completed(request, result);
+        assert_eq!(ctx.undo_steps(), 1);
+
+        ctx.reconnect();
+        assert_eq!(ctx.undo_steps(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_batch_captures_at_bookmarks() {
+        let (mut ctx, mut completed) = offline_context();
+        ctx.start_batch();
+        assert!(!ctx.is_busy());
+
+        for x in 1..=3 {
+            ctx.config
+                .set_bookmark(format!("P{}", x), OpenFlexurePosition { x, y: 0, z: 0 });
+        }
+        ctx.start_batch();
+        for x in 1..=2 {
+            let Some(AppEvent::Completed { request, result }) = completed.recv().await else {
+                panic!("request did not complete");
+            };
+            assert_eq!(request, Request::CaptureAt { x, y: 0, z: 0 });
+            if x == 2 {
+                // the last position is dropped
+                ctx.stop();
+            }
+            ctx.completed(request, result);
+        }
+        assert!(!ctx.is_busy());
+    }
+}
@@ -0,0 +1,354 @@
+use log::error;
+
+use super::{
+    Context, Screen, Transition,
+    calibration::CalibrationScreen,
+    content_area, display_error, draw_frame,
+    edit::EditScreen,
+    limits::LimitsScreen,
+    step_selection,
+    widgets::{List, Regions, Spinner, ValueRow, Widget},
+};
+use crate::{
+    client::OpenflexureAxis, display::Panel, display::ili9341::Orientation, input::InputEvent,
+};
+
+/// Entries of the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    StepSize,
+    FineStepSize,
+    Server,
+    Brightness,
+    SleepAfter,
+    Orientation,
+    InvertEncoder,
+    Language,
+    Unit,
+    Calibration,
+    Limits,
+    Back,
+}
+
+const SETTINGS: [Setting; 12] = [
+    Setting::StepSize,
+    Setting::FineStepSize,
+    Setting::Server,
+    Setting::Brightness,
+    Setting::SleepAfter,
+    Setting::Orientation,
+    Setting::InvertEncoder,
+    Setting::Language,
+    Setting::Unit,
+    Setting::Calibration,
+    Setting::Limits,
+    Setting::Back,
+];
+
+impl Setting {
+    fn name(self) -> &'static str {
+        match self {
+            Self::StepSize => "Step",
+            Self::FineStepSize => "Fine step",
+            Self::Server => "Server",
+            Self::Brightness => "Brightness",
+            Self::SleepAfter => "Sleep after",
+            Self::Orientation => "Rotate",
+            Self::InvertEncoder => "Invert enc.",
+            Self::Language => "Language",
+            Self::Unit => "Unit",
+            Self::Calibration => "Calibration",
+            Self::Limits => "Limits",
+            Self::Back => "Back",
+        }
+    }
+}
+
+/// Largest step size which can be set, in motor steps
+const MAX_STEP_SIZE: i64 = 5000;
+/// Largest step size which can be set in µm
+const MAX_STEP_UM: i64 = 1000;
+
+/// Choices for the time without input before the display sleeps, `0` never
+/// sleeps.
+const SLEEP_AFTER_SECS: [u64; 6] = [60, 120, 300, 600, 1800, 0];
+
+fn next_sleep_after(secs: u64) -> u64 {
+    let idx = SLEEP_AFTER_SECS
+        .iter()
+        .position(|choice| *choice == secs)
+        .map_or(0, |idx| (idx + 1) % SLEEP_AFTER_SECS.len());
+    SLEEP_AFTER_SECS[idx]
+}
+
+/// Backlight level in percent, the config stores the PWM level.
+fn brightness_percent(level: u8) -> i64 {
+    (i64::from(level) * 100 + 127) / 255
+}
+
+fn brightness_level(percent: i64) -> u8 {
+    (percent.clamp(0, 100) * 255 / 100) as u8
+}
+
+/// Short name of the orientation, fitting on the small panels
+fn orientation_label(orientation: Orientation) -> &'static str {
+    match orientation {
+        Orientation::Landscape => "Land",
+        Orientation::LandscapeFlipped => "Land 180",
+        Orientation::Portrait => "Port",
+        Orientation::PortraitFlipped => "Port 180",
+    }
+}
+
+fn next_orientation(orientation: Orientation) -> Orientation {
+    match orientation {
+        Orientation::Landscape => Orientation::LandscapeFlipped,
+        Orientation::LandscapeFlipped => Orientation::Portrait,
+        Orientation::Portrait => Orientation::PortraitFlipped,
+        Orientation::PortraitFlipped => Orientation::Landscape,
+    }
+}
+
+/// Whether the step size in µm is used, by the axes which are shown in µm
+/// or mm. The others use the step size in motor steps, which is set while
+/// positions are shown in steps.
+fn physical_steps<D>(ctx: &Context<D>) -> bool {
+    let units = &ctx.config.units;
+    OpenflexureAxis::ALL
+        .into_iter()
+        .any(|axis| units.is_physical(axis))
+}
+
+/// Step sizes which are used, e.g. `10 µm 200 st` if only some axes are
+/// calibrated.
+fn step_text<D>(um: i64, steps: i64, ctx: &Context<D>) -> String {
+    let units = &ctx.config.units;
+    let mut parts = Vec::new();
+    if physical_steps(ctx) {
+        parts.push(format!("{} µm", um));
+    }
+    if !OpenflexureAxis::ALL
+        .into_iter()
+        .all(|axis| units.is_physical(axis))
+    {
+        parts.push(format!("{} st", steps));
+    }
+    parts.join(" ")
+}
+
+/// Settings which are applied right away and stored in the config file.
+pub struct SettingsScreen {
+    settings_idx: u32,
+    regions: Regions,
+}
+
+impl SettingsScreen {
+    pub fn new() -> Self {
+        Self {
+            settings_idx: 0,
+            regions: Regions::new(),
+        }
+    }
+
+    fn setting_text<D>(setting: Setting, ctx: &Context<D>) -> String
+    where
+        D: Panel,
+    {
+        let config = &ctx.config;
+        match setting {
+            Setting::StepSize => step_text(config.steps.step_um, config.steps.step_size, ctx),
+            Setting::FineStepSize => {
+                step_text(config.steps.fine_step_um, config.steps.fine_step_size, ctx)
+            }
+            Setting::Server => match config.server_idx() {
+                Some(idx) => config.servers[idx].name.clone(),
+                None => ctx.tr("Custom").to_string(),
+            },
+            Setting::Brightness => format!("{} %", brightness_percent(config.power.brightness)),
+            Setting::SleepAfter => match config.power.sleep_after_secs {
+                0 => ctx.tr("Off").to_string(),
+                secs => format!("{} min", secs / 60),
+            },
+            Setting::Orientation => ctx.tr(orientation_label(config.orientation)).to_string(),
+            Setting::InvertEncoder if config.input.invert_encoder => ctx.tr("On").to_string(),
+            Setting::InvertEncoder => ctx.tr("Off").to_string(),
+            Setting::Language => config.language.name().to_string(),
+            Setting::Unit => config.units.unit.symbol().to_string(),
+            Setting::Calibration | Setting::Limits => ">".to_string(),
+            Setting::Back => "<".to_string(),
+        }
+    }
+
+    /// Opens a screen to change a number, the value is stored in the config
+    /// file when it is confirmed.
+    fn edit<D>(
+        setting: Setting,
+        spinner: Spinner,
+        apply: impl FnOnce(&mut Context<D>, i64) + 'static,
+    ) -> Transition<D>
+    where
+        D: Panel,
+    {
+        Transition::Push(Box::new(EditScreen::new(
+            setting.name(),
+            spinner,
+            |ctx: &mut Context<D>, value| {
+                apply(ctx, value);
+                ctx.save_config();
+            },
+        )))
+    }
+
+    /// Changes the selected setting, applies it right away and stores it in
+    /// the config file. Numbers are changed on a screen of their own.
+    fn change_setting<D>(&mut self, ctx: &mut Context<D>) -> Transition<D>
+    where
+        D: Panel,
+    {
+        let setting = SETTINGS[self.settings_idx as usize];
+        match setting {
+            Setting::StepSize if physical_steps(ctx) => {
+                let spinner = Spinner::new(ctx.config.steps.step_um, 1, 1, MAX_STEP_UM);
+                return Self::edit(setting, spinner.with_unit("µm"), |ctx, value| {
+                    ctx.config.steps.step_um = value;
+                });
+            }
+            Setting::FineStepSize if physical_steps(ctx) => {
+                let spinner = Spinner::new(ctx.config.steps.fine_step_um, 1, 1, MAX_STEP_UM);
+                return Self::edit(setting, spinner.with_unit("µm"), |ctx, value| {
+                    ctx.config.steps.fine_step_um = value;
+                });
+            }
+            Setting::StepSize => {
+                let spinner = Spinner::new(ctx.config.steps.step_size, 10, 10, MAX_STEP_SIZE);
+                return Self::edit(setting, spinner.with_unit("st"), |ctx, value| {
+                    ctx.config.steps.step_size = value;
+                });
+            }
+            Setting::FineStepSize => {
+                let spinner = Spinner::new(ctx.config.steps.fine_step_size, 1, 1, MAX_STEP_SIZE);
+                return Self::edit(setting, spinner.with_unit("st"), |ctx, value| {
+                    ctx.config.steps.fine_step_size = value;
+                });
+            }
+            Setting::Server => {
+                ctx.config.select_next_server();
+                ctx.reconnect();
+            }
+            Setting::Brightness => {
+                // a dark backlight could not be seen to change it back
+                let percent = brightness_percent(ctx.config.power.brightness);
+                let spinner = Spinner::new(percent, 10, 10, 100);
+                return Self::edit(setting, spinner.with_unit("%"), |ctx, value| {
+                    ctx.config.power.brightness = brightness_level(value);
+                });
+            }
+            Setting::SleepAfter => {
+                ctx.config.power.sleep_after_secs =
+                    next_sleep_after(ctx.config.power.sleep_after_secs);
+            }
+            Setting::Orientation => {
+                let orientation = next_orientation(ctx.config.orientation);
+                if let Err(e) = ctx.display.set_orientation(orientation) {
+                    error!("failed to rotate display: {:?}", e);
+                    return Transition::Stay;
+                }
+                ctx.config.orientation = orientation;
+                // the layout depends on the size of the display
+                self.regions.invalidate();
+            }
+            Setting::InvertEncoder => {
+                ctx.config.input.invert_encoder = !ctx.config.input.invert_encoder;
+            }
+            Setting::Language => ctx.config.language = ctx.config.language.next(),
+            Setting::Unit => ctx.config.units.unit = ctx.config.units.unit.next(),
+            Setting::Calibration => {
+                return Transition::Push(Box::new(CalibrationScreen::new()));
+            }
+            Setting::Limits => return Transition::Push(Box::new(LimitsScreen::new())),
+            Setting::Back => return Transition::Pop,
+        }
+
+        ctx.save_config();
+        Transition::Stay
+    }
+}
+
+impl Default for SettingsScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Screen<D> for SettingsScreen
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "settings"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        match event {
+            InputEvent::Up => {
+                self.settings_idx = step_selection(self.settings_idx, SETTINGS.len(), true);
+            }
+            InputEvent::Down => {
+                self.settings_idx = step_selection(self.settings_idx, SETTINGS.len(), false);
+            }
+            InputEvent::Select => return self.change_setting(ctx),
+            InputEvent::LongPress => return Transition::Pop,
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        if self.regions.take_invalidated() {
+            draw_frame(ctx)?;
+        }
+        let rows: Vec<_> = SETTINGS
+            .iter()
+            .map(|setting| ValueRow::new(ctx.tr(setting.name()), Self::setting_text(*setting, ctx)))
+            .collect();
+
+        List::new(&rows, self.settings_idx)
+            .draw_changed(
+                content_area(ctx),
+                &ctx.theme,
+                &mut ctx.display,
+                &mut self.regions,
+            )
+            .map_err(display_error)?;
+        self.regions
+            .clear_stale(&ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+
+    fn on_enter(&mut self, _ctx: &mut Context<D>) {
+        self.regions.invalidate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_sleep_after() {
+        assert_eq!(next_sleep_after(300), 600);
+        assert_eq!(next_sleep_after(0), 60);
+        // values set in the config file start over
+        assert_eq!(next_sleep_after(42), 60);
+    }
+
+    #[test]
+    fn test_brightness_percent() {
+        assert_eq!(brightness_percent(u8::MAX), 100);
+        assert_eq!(brightness_level(100), u8::MAX);
+        for percent in (10..=100).step_by(10) {
+            assert_eq!(brightness_percent(brightness_level(percent)), percent);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::{Widget, row_height, wrap};
+use crate::{
+    display::Panel,
+    input::InputEvent,
+    theme::Theme,
+    ui::{Context, Screen, Transition, display_error},
+};
+
+/// Box asking to confirm an action, with a "No" and a "Yes" button.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConfirmDialog<'a> {
+    pub title: &'a str,
+    pub message: &'a str,
+    /// The "Yes" button is selected
+    pub confirm: bool,
+}
+
+impl<'a> ConfirmDialog<'a> {
+    pub fn new(title: &'a str, message: &'a str, confirm: bool) -> Self {
+        Self {
+            title,
+            message,
+            confirm,
+        }
+    }
+}
+
+impl Widget for ConfirmDialog<'_> {
+    /// Draws the dialog centered in `area`, the rest of the area is kept.
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let char_size = theme.font.character_size;
+        let row_height = row_height(theme);
+        let padding = theme.border_width + theme.margin;
+        let width = area.size.width.saturating_sub(2 * theme.margin);
+        let columns = (width.saturating_sub(2 * padding) / char_size.width) as usize;
+
+        // the title and the buttons take one row each
+        let max_lines = (area.size.height.saturating_sub(2 * padding) / row_height)
+            .saturating_sub(2)
+            .max(1);
+        let mut lines = wrap(self.message, columns);
+        lines.truncate(max_lines as usize);
+
+        let height = (lines.len() as u32 + 2) * row_height + 2 * padding;
+        let dialog = Rectangle::with_center(area.center(), Size::new(width, height));
+        dialog
+            .into_styled(
+                PrimitiveStyleBuilder::new()
+                    .fill_color(theme.background)
+                    .stroke_color(theme.text)
+                    .stroke_width(theme.border_width)
+                    .build(),
+            )
+            .draw(target)?;
+
+        let center = TextStyleBuilder::new()
+            .baseline(Baseline::Top)
+            .alignment(Alignment::Center)
+            .build();
+        let text_top = |row: u32| {
+            dialog.top_left.y + (padding + row * row_height + theme.row_spacing / 2) as i32
+        };
+        Text::with_text_style(
+            self.title,
+            Point::new(dialog.center().x, text_top(0)),
+            MonoTextStyle::new(theme.font, theme.accent),
+            center,
+        )
+        .draw(target)?;
+        for (idx, line) in lines.iter().enumerate() {
+            Text::with_text_style(
+                line,
+                Point::new(dialog.center().x, text_top(idx as u32 + 1)),
+                MonoTextStyle::new(theme.font, theme.text),
+                center,
+            )
+            .draw(target)?;
+        }
+
+        let buttons_top = text_top(lines.len() as u32 + 1) - theme.row_spacing as i32 / 2;
+        let button_width = dialog.size.width / 2 - padding;
+        for (idx, (label, selected)) in [("No", !self.confirm), ("Yes", self.confirm)]
+            .into_iter()
+            .enumerate()
+        {
+            let button = Rectangle::new(
+                Point::new(
+                    dialog.top_left.x + (padding + idx as u32 * button_width) as i32,
+                    buttons_top,
+                ),
+                Size::new(button_width, row_height),
+            );
+            let text_color = if selected {
+                button
+                    .into_styled(PrimitiveStyle::with_fill(theme.selection_background))
+                    .draw(target)?;
+                theme.selection_text
+            } else {
+                theme.text
+            };
+            Text::with_text_style(
+                label,
+                Point::new(
+                    button.center().x,
+                    button.top_left.y + theme.row_spacing as i32 / 2,
+                ),
+                MonoTextStyle::new(theme.font, text_color),
+                center,
+            )
+            .draw(target)?;
+        }
+
+        Ok(())
+    }
+}
+
+type ConfirmAction<D> = Box<dyn FnOnce(&mut Context<D>)>;
+
+/// Modal screen showing a [`ConfirmDialog`] over the previous screen. The
+/// action only runs if "Yes" is selected, a long press cancels.
+pub struct ConfirmScreen<D> {
+    title: String,
+    message: String,
+    confirm: bool,
+    on_confirm: Option<ConfirmAction<D>>,
+}
+
+impl<D> ConfirmScreen<D> {
+    pub fn new(
+        title: impl Into<String>,
+        message: impl Into<String>,
+        on_confirm: impl FnOnce(&mut Context<D>) + 'static,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            // the safe choice is preselected
+            confirm: false,
+            on_confirm: Some(Box::new(on_confirm)),
+        }
+    }
+}
+
+impl<D> Screen<D> for ConfirmScreen<D>
+where
+    D: Panel,
+{
+    fn name(&self) -> &'static str {
+        "confirm"
+    }
+
+    fn handle(&mut self, event: InputEvent, ctx: &mut Context<D>) -> Transition<D> {
+        match event {
+            InputEvent::Up | InputEvent::Down => {
+                self.confirm = !self.confirm;
+                Transition::Stay
+            }
+            InputEvent::Select => {
+                if self.confirm
+                    && let Some(on_confirm) = self.on_confirm.take()
+                {
+                    on_confirm(ctx);
+                }
+                Transition::Pop
+            }
+            InputEvent::LongPress => Transition::Pop,
+            _ => Transition::Stay,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut Context<D>) -> anyhow::Result<()> {
+        let area = ctx.display.bounding_box();
+        ConfirmDialog::new(&self.title, &self.message, self.confirm)
+            .draw(area, &ctx.theme, &mut ctx.display)
+            .map_err(display_error)?;
+        ctx.display.flush().map_err(display_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widgets::snapshot;
+
+    #[test]
+    fn test_confirm_dialog() {
+        let dialog = ConfirmDialog::new("Home", "Move the stage to 0?", true);
+        insta::assert_snapshot!(snapshot::render(&dialog, Size::new(128, 64)));
+    }
+}
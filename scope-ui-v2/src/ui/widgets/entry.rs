@@ -0,0 +1,361 @@
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+use super::{Widget, row_height};
+use crate::{input::InputEvent, theme::Theme};
+
+/// State of an entry after an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryOutcome {
+    Editing,
+    /// Select was pressed on the last position
+    Done,
+    /// A long press on the first position
+    Cancelled,
+}
+
+/// Positions of an entry which are changed with the encoder one by one.
+trait Cursor {
+    fn step(&mut self, up: bool);
+    fn advance(&mut self) -> bool;
+    fn back(&mut self) -> bool;
+}
+
+/// Turning the encoder changes the position under the cursor. Select moves to
+/// the next position and completes the entry on the last one, a long press
+/// goes back a position and cancels on the first one.
+fn handle_input(entry: &mut impl Cursor, event: InputEvent) -> EntryOutcome {
+    match event {
+        InputEvent::Up | InputEvent::FineUp => entry.step(true),
+        InputEvent::Down | InputEvent::FineDown => entry.step(false),
+        InputEvent::Select if !entry.advance() => return EntryOutcome::Done,
+        InputEvent::LongPress if !entry.back() => return EntryOutcome::Cancelled,
+        _ => {}
+    }
+    EntryOutcome::Editing
+}
+
+/// Number entered digit by digit with the encoder, drawn as `-001200`.
+/// Turning the encoder changes the sign or the digit under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NumberEntry {
+    negative: bool,
+    /// Digits from the most significant one, with leading zeros
+    digits: Vec<u8>,
+    /// Position of the cursor, the sign comes first if negative numbers are
+    /// allowed
+    cursor: usize,
+    min: i64,
+    max: i64,
+}
+
+impl NumberEntry {
+    /// Starts with `value`, with as many digits as the limits need.
+    pub fn new(value: i64, min: i64, max: i64) -> Self {
+        let width = min.unsigned_abs().max(max.unsigned_abs()).to_string().len();
+        let value = value.clamp(min, max);
+        let digits = format!("{:0width$}", value.unsigned_abs(), width = width)
+            .bytes()
+            .map(|digit| digit - b'0')
+            .collect();
+        Self {
+            negative: value < 0,
+            digits,
+            cursor: 0,
+            min,
+            max,
+        }
+    }
+
+    fn signed(&self) -> bool {
+        self.min < 0
+    }
+
+    /// Index of the digit under the cursor, `None` on the sign
+    fn digit_idx(&self) -> Option<usize> {
+        self.cursor.checked_sub(self.signed() as usize)
+    }
+
+    pub fn value(&self) -> i64 {
+        let value = self
+            .digits
+            .iter()
+            .fold(0i64, |value, digit| value * 10 + i64::from(*digit));
+        if self.negative { -value } else { value }
+    }
+
+    /// Whether the entered value is within the limits.
+    pub fn in_range(&self) -> bool {
+        (self.min..=self.max).contains(&self.value())
+    }
+
+    /// Changes the entry with an input of the encoder or the button.
+    pub fn handle(&mut self, event: InputEvent) -> EntryOutcome {
+        handle_input(self, event)
+    }
+
+    fn text(&self) -> String {
+        let sign = match (self.signed(), self.negative) {
+            (false, _) => "",
+            (true, false) => "+",
+            (true, true) => "-",
+        };
+        let digits: String = self
+            .digits
+            .iter()
+            .map(|digit| char::from(b'0' + digit))
+            .collect();
+        format!("{}{}", sign, digits)
+    }
+}
+
+impl Cursor for NumberEntry {
+    /// Changes the sign or the digit under the cursor, digits wrap around.
+    fn step(&mut self, up: bool) {
+        match self.digit_idx() {
+            None => self.negative = !self.negative,
+            Some(idx) => {
+                let delta = if up { 1 } else { 9 };
+                self.digits[idx] = (self.digits[idx] + delta) % 10;
+            }
+        }
+    }
+
+    /// Moves the cursor to the next position. Returns `false` if it already
+    /// is on the last digit, i.e. the number is complete.
+    fn advance(&mut self) -> bool {
+        let last = self.signed() as usize + self.digits.len() - 1;
+        if self.cursor >= last {
+            return false;
+        }
+        self.cursor += 1;
+        true
+    }
+
+    /// Moves the cursor to the previous position. Returns `false` if it
+    /// already is on the first one.
+    fn back(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        true
+    }
+}
+
+impl Widget for NumberEntry {
+    /// Draws the number centered in `area` with the position under the
+    /// cursor highlighted.
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        draw_with_cursor(&self.text(), self.cursor, area, theme, target)
+    }
+}
+
+/// Characters which can be entered into a [`TextEntry`], a blank ends the
+/// text
+const CHARSET: &[u8] = b" ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-";
+
+/// Short text entered character by character with the encoder, e.g. the name
+/// of a bookmark. Turning the encoder changes the character under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEntry {
+    /// Indices into [`CHARSET`]
+    chars: Vec<usize>,
+    cursor: usize,
+}
+
+impl TextEntry {
+    /// Starts with `text` in upper case, with room for `len` characters.
+    pub fn new(text: &str, len: usize) -> Self {
+        let mut chars: Vec<_> = text
+            .to_ascii_uppercase()
+            .bytes()
+            .map(|char| CHARSET.iter().position(|c| *c == char).unwrap_or(0))
+            .take(len)
+            .collect();
+        chars.resize(len.max(1), 0);
+        Self { chars, cursor: 0 }
+    }
+
+    /// The entered text without the trailing blanks.
+    pub fn text(&self) -> String {
+        let text: String = self
+            .chars
+            .iter()
+            .map(|idx| char::from(CHARSET[*idx]))
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    /// Changes the entry with an input of the encoder or the button.
+    pub fn handle(&mut self, event: InputEvent) -> EntryOutcome {
+        handle_input(self, event)
+    }
+}
+
+impl Cursor for TextEntry {
+    /// Changes the character under the cursor, wrapping around the charset.
+    fn step(&mut self, up: bool) {
+        let char = &mut self.chars[self.cursor];
+        let delta = if up { 1 } else { CHARSET.len() - 1 };
+        *char = (*char + delta) % CHARSET.len();
+    }
+
+    /// Moves the cursor to the next character. Returns `false` if the text
+    /// is complete, i.e. the cursor is on a blank or on the last character.
+    fn advance(&mut self) -> bool {
+        if self.chars[self.cursor] == 0 || self.cursor + 1 == self.chars.len() {
+            return false;
+        }
+        self.cursor += 1;
+        true
+    }
+
+    /// Moves the cursor to the previous character. Returns `false` if it
+    /// already is on the first one.
+    fn back(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        true
+    }
+}
+
+impl Widget for TextEntry {
+    /// Draws the text centered in `area` with the character under the
+    /// cursor highlighted.
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let text: String = self
+            .chars
+            .iter()
+            .map(|idx| char::from(CHARSET[*idx]))
+            .collect();
+        draw_with_cursor(&text, self.cursor, area, theme, target)
+    }
+}
+
+/// Draws `text` centered in a row of `area`, the character at `cursor` is
+/// highlighted in the color of the selection marker, which stands out on the
+/// selection background of all themes.
+fn draw_with_cursor<D>(
+    text: &str,
+    cursor: usize,
+    area: Rectangle,
+    theme: &Theme,
+    target: &mut D,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let char_size = theme.font.character_size;
+    let width = text.len() as u32 * char_size.width;
+    let row_height = row_height(theme);
+    let top_left = area.center() - Point::new(width as i32 / 2, row_height as i32 / 2);
+
+    for (idx, char) in text.char_indices() {
+        let cell = Rectangle::new(
+            top_left + Point::new((idx as u32 * char_size.width) as i32, 0),
+            Size::new(char_size.width, row_height),
+        );
+        // a blank under the cursor would not be visible on all themes
+        let char = if idx == cursor && char == ' ' {
+            '_'
+        } else {
+            char
+        };
+        let color = if idx == cursor {
+            cell.into_styled(PrimitiveStyle::with_fill(theme.selection_background))
+                .draw(target)?;
+            theme.selection_marker
+        } else {
+            theme.text
+        };
+        Text::with_baseline(
+            char.encode_utf8(&mut [0; 4]),
+            cell.top_left + Point::new(0, theme.row_spacing as i32 / 2),
+            MonoTextStyle::new(theme.font, color),
+            Baseline::Top,
+        )
+        .draw(target)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widgets::snapshot;
+
+    #[test]
+    fn test_number_entry_digits() {
+        let mut entry = NumberEntry::new(1200, -50_000, 50_000);
+        assert_eq!(entry.text(), "+01200");
+
+        // sign
+        entry.step(false);
+        assert!(entry.advance());
+        // digits wrap around
+        entry.step(false);
+        assert_eq!(entry.value(), -91200);
+        assert!(!entry.in_range());
+
+        while entry.advance() {}
+        entry.step(true);
+        assert_eq!(entry.value(), -91201);
+        assert!(entry.back());
+
+        let entry = NumberEntry::new(-5, 0, 999);
+        assert_eq!(entry.text(), "000");
+        assert_eq!(entry.digit_idx(), Some(0));
+    }
+
+    #[test]
+    fn test_number_entry() {
+        let mut entry = NumberEntry::new(-120, -999, 999);
+        entry.advance();
+        insta::assert_snapshot!(snapshot::render(&entry, Size::new(48, 12)));
+    }
+
+    #[test]
+    fn test_text_entry() {
+        let mut entry = TextEntry::new("p1", 4);
+        assert_eq!(entry.text(), "P1");
+        assert!(entry.advance());
+        entry.step(true);
+        assert_eq!(entry.text(), "P2");
+        assert!(entry.advance());
+        // a blank ends the text
+        assert!(!entry.advance());
+        entry.step(false);
+        assert_eq!(entry.text(), "P2-");
+        assert!(entry.advance());
+        assert!(!entry.advance());
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut entry = NumberEntry::new(7, 0, 99);
+        assert_eq!(entry.handle(InputEvent::Select), EntryOutcome::Editing);
+        assert_eq!(entry.handle(InputEvent::FineUp), EntryOutcome::Editing);
+        assert_eq!(entry.handle(InputEvent::Select), EntryOutcome::Done);
+        assert_eq!(entry.value(), 8);
+
+        let mut entry = TextEntry::new("A", 2);
+        assert_eq!(entry.handle(InputEvent::Select), EntryOutcome::Editing);
+        assert_eq!(entry.handle(InputEvent::LongPress), EntryOutcome::Editing);
+        assert_eq!(entry.handle(InputEvent::LongPress), EntryOutcome::Cancelled);
+    }
+}
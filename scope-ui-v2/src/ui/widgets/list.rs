@@ -0,0 +1,285 @@
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::{Regions, Widget, row_height};
+use crate::theme::Theme;
+
+/// Row with a label on the left and a value with an optional unit right
+/// aligned, e.g. `X Axis      1200 st`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValueRow<'a> {
+    pub label: &'a str,
+    pub value: String,
+    pub unit: Option<&'a str>,
+}
+
+impl<'a> ValueRow<'a> {
+    pub fn new(label: &'a str, value: impl ToString) -> Self {
+        Self {
+            label,
+            value: value.to_string(),
+            unit: None,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: &'a str) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    fn value_text(&self) -> String {
+        match self.unit {
+            Some(unit) => format!("{} {}", self.value, unit),
+            None => self.value.clone(),
+        }
+    }
+
+    /// Draws the row, a selected row is highlighted and marked with `>`.
+    fn draw_row<D>(
+        &self,
+        area: Rectangle,
+        selected: bool,
+        theme: &Theme,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let char_size = theme.font.character_size;
+        let text_top = area.top_left + Point::new(0, theme.row_spacing as i32 / 2);
+        let left = TextStyleBuilder::new().baseline(Baseline::Top).build();
+        let right = TextStyleBuilder::new()
+            .baseline(Baseline::Top)
+            .alignment(Alignment::Right)
+            .build();
+
+        let text_color = if selected {
+            area.into_styled(PrimitiveStyle::with_fill(theme.selection_background))
+                .draw(target)?;
+            Text::with_text_style(
+                ">",
+                text_top,
+                MonoTextStyle::new(theme.font, theme.selection_marker),
+                left,
+            )
+            .draw(target)?;
+
+            theme.selection_text
+        } else {
+            theme.text
+        };
+
+        let text_style = MonoTextStyle::new(theme.font, text_color);
+        Text::with_text_style(
+            self.label,
+            text_top + Point::new(2 * char_size.width as i32, 0),
+            text_style,
+            left,
+        )
+        .draw(target)?;
+
+        Text::with_text_style(
+            &self.value_text(),
+            text_top + Point::new(area.size.width as i32 - 1, 0),
+            text_style,
+            right,
+        )
+        .draw(target)?;
+
+        Ok(())
+    }
+}
+
+impl Widget for ValueRow<'_> {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let area = Rectangle::new(area.top_left, Size::new(area.size.width, row_height(theme)));
+        self.draw_row(area, false, theme, target)
+    }
+}
+
+/// Selectable list of rows, vertically centered in its area. If not all rows
+/// fit, the list scrolls with the selection.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct List<'a> {
+    rows: &'a [ValueRow<'a>],
+    selected: u32,
+    footer: Option<(&'a str, Rgb565)>,
+}
+
+/// Visible row of a [`List`], drawn as widget of its own so only changed
+/// rows are drawn again.
+#[derive(Hash)]
+struct ListRow<'a> {
+    row: &'a ValueRow<'a>,
+    selected: bool,
+}
+
+impl Widget for ListRow<'_> {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.row.draw_row(area, self.selected, theme, target)
+    }
+}
+
+/// Centered line below the rows of a [`List`]
+#[derive(Hash)]
+struct Footer<'a> {
+    text: &'a str,
+    color: Rgb565,
+}
+
+impl Widget for Footer<'_> {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let center = TextStyleBuilder::new()
+            .baseline(Baseline::Top)
+            .alignment(Alignment::Center)
+            .build();
+        Text::with_text_style(
+            self.text,
+            Point::new(
+                area.center().x,
+                area.top_left.y + theme.row_spacing as i32 / 2,
+            ),
+            MonoTextStyle::new(theme.font, self.color),
+            center,
+        )
+        .draw(target)?;
+        Ok(())
+    }
+}
+
+impl<'a> List<'a> {
+    pub fn new(rows: &'a [ValueRow<'a>], selected: u32) -> Self {
+        Self {
+            rows,
+            selected,
+            footer: None,
+        }
+    }
+
+    /// Adds a centered line below the rows, e.g. to show a mode.
+    pub fn with_footer(mut self, text: &'a str, color: Rgb565) -> Self {
+        self.footer = Some((text, color));
+        self
+    }
+
+    /// Splits `area` into the visible rows and the footer.
+    fn layout(&self, area: Rectangle, theme: &Theme) -> ListLayout<'_> {
+        let row_height = row_height(theme);
+        let footer_rows = self.footer.is_some() as u32;
+        let visible = (area.size.height / row_height)
+            .saturating_sub(footer_rows)
+            .min(self.rows.len() as u32)
+            .max(1);
+        let first = (self.selected + 1).saturating_sub(visible);
+        let top = area.top_left.y
+            + (area
+                .size
+                .height
+                .saturating_sub((visible + footer_rows) * row_height)
+                / 2) as i32;
+        let row_area = |row_idx: u32| {
+            Rectangle::new(
+                Point::new(area.top_left.x, top + (row_idx * row_height) as i32),
+                Size::new(area.size.width, row_height),
+            )
+        };
+
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(first as usize)
+            .take(visible as usize)
+            .zip(0..)
+            .map(|((idx, row), row_idx)| {
+                let selected = idx as u32 == self.selected;
+                (row_area(row_idx), ListRow { row, selected })
+            })
+            .collect();
+        let footer = self
+            .footer
+            .map(|(text, color)| (row_area(visible), Footer { text, color }));
+
+        ListLayout { rows, footer }
+    }
+}
+
+/// Areas of the parts of a [`List`]
+struct ListLayout<'a> {
+    rows: Vec<(Rectangle, ListRow<'a>)>,
+    footer: Option<(Rectangle, Footer<'a>)>,
+}
+
+impl Widget for List<'_> {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let ListLayout { rows, footer } = self.layout(area, theme);
+        for (row_area, row) in rows {
+            row.draw(row_area, theme, target)?;
+        }
+        if let Some((footer_area, footer)) = footer {
+            footer.draw(footer_area, theme, target)?;
+        }
+        Ok(())
+    }
+
+    /// Draws the rows and the footer which changed.
+    fn draw_changed<D>(
+        &self,
+        area: Rectangle,
+        theme: &Theme,
+        target: &mut D,
+        regions: &mut Regions,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let ListLayout { rows, footer } = self.layout(area, theme);
+        for (row_area, row) in rows {
+            regions.draw(&row, row_area, theme, target)?;
+        }
+        if let Some((footer_area, footer)) = footer {
+            regions.draw(&footer, footer_area, theme, target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widgets::snapshot;
+
+    #[test]
+    fn test_value_row() {
+        let row = ValueRow::new("Z", -1200).with_unit("st");
+        insta::assert_snapshot!(snapshot::render(&row, Size::new(64, 10)));
+    }
+
+    #[test]
+    fn test_list_scrolls_with_selection() {
+        let rows: Vec<_> = ["A", "B", "C", "D", "E", "F"]
+            .into_iter()
+            .zip(1..)
+            .map(|(label, value)| ValueRow::new(label, value))
+            .collect();
+        let list = List::new(&rows, 4).with_footer("Mode", Rgb565::WHITE);
+        insta::assert_snapshot!(snapshot::render(&list, Size::new(48, 40)));
+    }
+}
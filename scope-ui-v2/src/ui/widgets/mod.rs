@@ -0,0 +1,126 @@
+//! Building blocks of the screens.
+//!
+//! Widgets only describe what to show, they are created for every frame and
+//! drawn into an area of the display with the colors and fonts of the
+//! [`Theme`]. State like the selected entry stays in the screens.
+//!
+//! To avoid flicker, screens draw with [`Widget::draw_changed`], which only
+//! clears and draws the areas whose content changed since the last frame.
+
+use std::hash::Hash;
+
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+use crate::theme::Theme;
+
+mod dialog;
+mod entry;
+mod list;
+mod progress;
+mod regions;
+mod spinner;
+mod toast;
+
+pub use dialog::{ConfirmDialog, ConfirmScreen};
+pub use entry::{EntryOutcome, NumberEntry, TextEntry};
+pub use list::{List, ValueRow};
+pub use progress::ProgressBar;
+pub use regions::Regions;
+pub use spinner::Spinner;
+pub use toast::Toast;
+
+/// Part of a screen drawn into an area of the display.
+pub trait Widget {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>;
+
+    /// Draws the parts of the widget which changed since they were drawn
+    /// last with the same `regions`.
+    fn draw_changed<D>(
+        &self,
+        area: Rectangle,
+        theme: &Theme,
+        target: &mut D,
+        regions: &mut Regions,
+    ) -> Result<(), D::Error>
+    where
+        Self: Hash,
+        D: DrawTarget<Color = Rgb565>,
+    {
+        regions.draw(self, area, theme, target).map(|_| ())
+    }
+}
+
+/// Height of a line of text including the row spacing of the theme.
+pub fn row_height(theme: &Theme) -> u32 {
+    theme.font.character_size.height + theme.row_spacing
+}
+
+/// Splits `text` into lines of at most `columns` characters, breaking at
+/// spaces where possible.
+pub fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if !line.is_empty() && line.chars().count() + 1 + word.len() > columns {
+            lines.push(std::mem::take(&mut line));
+        }
+        // words longer than a line are split
+        while word.len() > columns {
+            let rest = word.split_off(columns);
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("Move the stage home?", 9),
+            ["Move the", "stage", "home?"]
+        );
+        assert_eq!(wrap("Unreachable", 4), ["Unre", "acha", "ble"]);
+        assert!(wrap("  ", 4).is_empty());
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod snapshot {
+    use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+    use super::Widget;
+    use crate::{
+        display::{ili9341::Orientation, simulated::SimulatedPanel},
+        theme::Theme,
+    };
+
+    /// Draws the widget on a monochrome display of `size` and dumps it as
+    /// text, so snapshots do not depend on the colors of the theme.
+    pub fn render(widget: &impl Widget, size: Size) -> String {
+        let theme = Theme::monochrome();
+        let mut display = SimulatedPanel::new(size, Orientation::Landscape);
+        widget
+            .draw(Rectangle::new(Point::zero(), size), &theme, &mut display)
+            .unwrap();
+        display.to_ascii(|color| theme.to_binary(color).is_on())
+    }
+}
@@ -0,0 +1,96 @@
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::{Widget, row_height};
+use crate::theme::Theme;
+
+/// Horizontal bar with the percentage right of it, e.g. for a running scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgressBar {
+    done: u32,
+    total: u32,
+}
+
+impl ProgressBar {
+    /// Creates a bar for `done` of `total` steps.
+    pub fn new(done: u32, total: u32) -> Self {
+        Self {
+            done: done.min(total),
+            total,
+        }
+    }
+
+    /// Done part from 0 to 1
+    fn progress(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
+
+impl Widget for ProgressBar {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let char_size = theme.font.character_size;
+        let height = row_height(theme).min(area.size.height);
+        let progress = self.progress();
+        let label = format!("{}%", (progress * 100.0).round() as u32);
+        // room for "100%" and a space
+        let label_width = 5 * char_size.width;
+
+        let bar = Rectangle::new(
+            area.top_left,
+            Size::new(area.size.width.saturating_sub(label_width), height),
+        );
+        bar.into_styled(PrimitiveStyle::with_stroke(theme.text, 1))
+            .draw(target)?;
+        let inner = bar.offset(-2);
+        let filled = Size::new(
+            (inner.size.width as f32 * progress).round() as u32,
+            inner.size.height,
+        );
+        Rectangle::new(inner.top_left, filled)
+            .into_styled(PrimitiveStyle::with_fill(theme.accent))
+            .draw(target)?;
+
+        let right = TextStyleBuilder::new()
+            .baseline(Baseline::Middle)
+            .alignment(Alignment::Right)
+            .build();
+        Text::with_text_style(
+            &label,
+            Point::new(area.top_left.x + area.size.width as i32 - 1, bar.center().y),
+            MonoTextStyle::new(theme.font, theme.text),
+            right,
+        )
+        .draw(target)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widgets::snapshot;
+
+    #[test]
+    fn test_progress_bar() {
+        insta::assert_snapshot!(snapshot::render(&ProgressBar::new(3, 8), Size::new(64, 10)));
+    }
+
+    #[test]
+    fn test_progress_is_clamped() {
+        assert_eq!(ProgressBar::new(5, 4).progress(), 1.0);
+        assert_eq!(ProgressBar::new(0, 0).progress(), 1.0);
+    }
+}
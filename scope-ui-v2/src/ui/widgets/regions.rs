@@ -0,0 +1,176 @@
+use std::{
+    collections::{HashMap, HashSet, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+};
+
+use embedded_graphics::{
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use super::Widget;
+use crate::theme::Theme;
+
+/// Remembers what was drawn into the areas of a screen, so only areas whose
+/// content changed are cleared and drawn again.
+///
+/// Widgets drawn through [`Regions::draw`] have to stay inside their area.
+#[derive(Debug)]
+pub struct Regions {
+    /// Hash of the widget drawn last into each area
+    drawn: HashMap<Rectangle, u64>,
+    /// Areas drawn since the last [`Regions::clear_stale`]
+    seen: HashSet<Rectangle>,
+    invalidated: bool,
+}
+
+impl Regions {
+    /// Creates the regions of a screen which was not drawn yet.
+    pub fn new() -> Self {
+        Self {
+            drawn: HashMap::new(),
+            seen: HashSet::new(),
+            invalidated: true,
+        }
+    }
+
+    /// Forgets what was drawn, e.g. after another screen covered the display.
+    pub fn invalidate(&mut self) {
+        self.drawn.clear();
+        self.seen.clear();
+        self.invalidated = true;
+    }
+
+    /// Returns whether the screen has to be drawn completely, once after
+    /// every [`Regions::invalidate`].
+    pub fn take_invalidated(&mut self) -> bool {
+        std::mem::take(&mut self.invalidated)
+    }
+
+    /// Draws `widget` into `area` unless the same widget was drawn there
+    /// last. Returns whether it was drawn.
+    pub fn draw<W, D>(
+        &mut self,
+        widget: &W,
+        area: Rectangle,
+        theme: &Theme,
+        target: &mut D,
+    ) -> Result<bool, D::Error>
+    where
+        W: Widget + Hash + ?Sized,
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.seen.insert(area);
+        let mut hasher = DefaultHasher::new();
+        widget.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.drawn.get(&area) == Some(&hash) {
+            return Ok(false);
+        }
+
+        area.into_styled(PrimitiveStyle::with_fill(theme.background))
+            .draw(target)?;
+        widget.draw(area, theme, target)?;
+        self.drawn.insert(area, hash);
+        Ok(true)
+    }
+
+    /// Clears the areas which were drawn before, but not since the last call,
+    /// e.g. the rows of a list which got shorter.
+    pub fn clear_stale<D>(&mut self, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let stale: Vec<_> = self
+            .drawn
+            .keys()
+            .filter(|area| !self.seen.contains(area))
+            .copied()
+            .collect();
+        for area in stale {
+            area.into_styled(PrimitiveStyle::with_fill(theme.background))
+                .draw(target)?;
+            self.drawn.remove(&area);
+        }
+        self.seen.clear();
+        Ok(())
+    }
+}
+
+impl Default for Regions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        display::{ili9341::Orientation, simulated::SimulatedPanel},
+        ui::widgets::ValueRow,
+    };
+
+    #[test]
+    fn test_only_changed_areas_are_drawn() {
+        let theme = Theme::monochrome();
+        let mut display = SimulatedPanel::new(Size::new(64, 32), Orientation::Landscape);
+        let mut regions = Regions::new();
+        let top = Rectangle::new(Point::zero(), Size::new(64, 10));
+        let bottom = Rectangle::new(Point::new(0, 10), Size::new(64, 10));
+        let marker = Point::new(63, 9);
+
+        assert!(regions.take_invalidated());
+        assert!(!regions.take_invalidated());
+        let frame = |regions: &mut Regions, display: &mut SimulatedPanel, x: i64| {
+            let drawn = (
+                regions
+                    .draw(&ValueRow::new("X", x), top, &theme, display)
+                    .unwrap(),
+                regions
+                    .draw(&ValueRow::new("Y", 0), bottom, &theme, display)
+                    .unwrap(),
+            );
+            regions.clear_stale(&theme, display).unwrap();
+            drawn
+        };
+
+        assert_eq!(frame(&mut regions, &mut display, 1), (true, true));
+        // a pixel which is only cleared if the top row is drawn again
+        Pixel(marker, Rgb565::WHITE).draw(&mut display).unwrap();
+        assert_eq!(frame(&mut regions, &mut display, 1), (false, false));
+        assert_eq!(display.pixel(marker), Some(Rgb565::WHITE));
+        assert_eq!(frame(&mut regions, &mut display, 2), (true, false));
+        assert_eq!(display.pixel(marker), Some(theme.background));
+
+        regions.invalidate();
+        assert!(regions.take_invalidated());
+        assert_eq!(frame(&mut regions, &mut display, 2), (true, true));
+    }
+
+    #[test]
+    fn test_stale_areas_are_cleared() {
+        let theme = Theme::monochrome();
+        let mut display = SimulatedPanel::new(Size::new(64, 32), Orientation::Landscape);
+        let mut regions = Regions::new();
+        let area = Rectangle::new(Point::zero(), Size::new(64, 10));
+
+        regions
+            .draw(&ValueRow::new("X", 8), area, &theme, &mut display)
+            .unwrap();
+        regions.clear_stale(&theme, &mut display).unwrap();
+        assert!(
+            display
+                .to_ascii(|color| color != theme.background)
+                .contains('#')
+        );
+
+        regions.clear_stale(&theme, &mut display).unwrap();
+        assert!(
+            !display
+                .to_ascii(|color| color != theme.background)
+                .contains('#')
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::{Widget, row_height};
+use crate::theme::Theme;
+
+/// Number changed in steps with the encoder, drawn as `< 200 st >`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Spinner {
+    pub value: i64,
+    pub step: i64,
+    pub min: i64,
+    pub max: i64,
+    pub unit: Option<&'static str>,
+}
+
+impl Spinner {
+    pub fn new(value: i64, step: i64, min: i64, max: i64) -> Self {
+        Self {
+            value: value.clamp(min, max),
+            step,
+            min,
+            max,
+            unit: None,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Changes the value by one step, staying within the limits.
+    pub fn step(&mut self, up: bool) {
+        let delta = if up { self.step } else { -self.step };
+        self.value = self.value.saturating_add(delta).clamp(self.min, self.max);
+    }
+
+    fn text(&self) -> String {
+        let less = if self.value > self.min { '<' } else { ' ' };
+        let more = if self.value < self.max { '>' } else { ' ' };
+        match self.unit {
+            Some(unit) => format!("{} {} {} {}", less, self.value, unit, more),
+            None => format!("{} {} {}", less, self.value, more),
+        }
+    }
+}
+
+impl Widget for Spinner {
+    /// Draws the value highlighted and centered in `area`.
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let row =
+            Rectangle::with_center(area.center(), Size::new(area.size.width, row_height(theme)));
+        row.into_styled(PrimitiveStyle::with_fill(theme.selection_background))
+            .draw(target)?;
+
+        let center = TextStyleBuilder::new()
+            .baseline(Baseline::Middle)
+            .alignment(Alignment::Center)
+            .build();
+        Text::with_text_style(
+            &self.text(),
+            row.center(),
+            MonoTextStyle::new(theme.font, theme.selection_text),
+            center,
+        )
+        .draw(target)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widgets::snapshot;
+
+    #[test]
+    fn test_spinner_limits() {
+        let mut spinner = Spinner::new(150, 100, 0, 300);
+        spinner.step(true);
+        assert_eq!(spinner.value, 250);
+        spinner.step(true);
+        assert_eq!(spinner.value, 300);
+        assert_eq!(spinner.text(), "< 300  ");
+
+        let spinner = Spinner::new(-5, 1, 0, 10).with_unit("mm");
+        assert_eq!(spinner.text(), "  0 mm >");
+    }
+
+    #[test]
+    fn test_spinner() {
+        let spinner = Spinner::new(20, 10, 0, 100).with_unit("st");
+        insta::assert_snapshot!(snapshot::render(&spinner, Size::new(64, 16)));
+    }
+}
@@ -0,0 +1,91 @@
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyleBuilder, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::{Widget, wrap};
+use crate::theme::Theme;
+
+/// Short notice drawn over the bottom of the screen, e.g. when a request
+/// failed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Toast<'a> {
+    pub message: &'a str,
+}
+
+impl<'a> Toast<'a> {
+    pub fn new(message: &'a str) -> Self {
+        Self { message }
+    }
+}
+
+impl Widget for Toast<'_> {
+    fn draw<D>(&self, area: Rectangle, theme: &Theme, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let char_size = theme.font.character_size;
+        let padding = theme.border_width + 1;
+        let columns = (area.size.width.saturating_sub(2 * padding) / char_size.width) as usize;
+        let mut lines = wrap(self.message, columns);
+        // at most two lines, so the toast does not hide the screen
+        lines.truncate(2);
+
+        let longest = lines.iter().map(|line| line.chars().count()).max();
+        let size = Size::new(
+            longest.unwrap_or(0) as u32 * char_size.width + 2 * padding,
+            lines.len() as u32 * char_size.height + 2 * padding,
+        );
+        let bottom_center = Point::new(
+            area.center().x,
+            area.top_left.y + area.size.height as i32 - 1,
+        );
+        let toast = Rectangle::new(
+            bottom_center - Point::new(size.width as i32 / 2, size.height as i32 - 1),
+            size,
+        );
+        toast
+            .into_styled(
+                PrimitiveStyleBuilder::new()
+                    .fill_color(theme.background)
+                    .stroke_color(theme.accent)
+                    .stroke_width(theme.border_width)
+                    .build(),
+            )
+            .draw(target)?;
+
+        let center = TextStyleBuilder::new()
+            .baseline(Baseline::Top)
+            .alignment(Alignment::Center)
+            .build();
+        for (idx, line) in lines.iter().enumerate() {
+            Text::with_text_style(
+                line,
+                Point::new(
+                    toast.center().x,
+                    toast.top_left.y + (padding + idx as u32 * char_size.height) as i32,
+                ),
+                MonoTextStyle::new(theme.font, theme.text),
+                center,
+            )
+            .draw(target)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widgets::snapshot;
+
+    #[test]
+    fn test_toast() {
+        let toast = Toast::new("Move failed");
+        insta::assert_snapshot!(snapshot::render(&toast, Size::new(96, 32)));
+    }
+}
@@ -0,0 +1,221 @@
+//! Conversion of motor steps into physical distances.
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::OpenflexureAxis;
+
+/// Unit positions and step sizes are shown in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    /// Motor steps, as sent to the stage
+    #[default]
+    Steps,
+    Micrometre,
+    Millimetre,
+}
+
+impl Unit {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Steps => "st",
+            Self::Micrometre => "µm",
+            Self::Millimetre => "mm",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Steps => Self::Micrometre,
+            Self::Micrometre => Self::Millimetre,
+            Self::Millimetre => Self::Steps,
+        }
+    }
+}
+
+/// Steps per µm of every axis, `None` for axes which are not calibrated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AxisScale {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+}
+
+impl AxisScale {
+    pub fn axis(&self, axis: OpenflexureAxis) -> Option<f64> {
+        match axis {
+            OpenflexureAxis::X => self.x,
+            OpenflexureAxis::Y => self.y,
+            OpenflexureAxis::Z => self.z,
+        }
+    }
+
+    pub fn set_axis(&mut self, axis: OpenflexureAxis, steps_per_um: Option<f64>) {
+        match axis {
+            OpenflexureAxis::X => self.x = steps_per_um,
+            OpenflexureAxis::Y => self.y = steps_per_um,
+            OpenflexureAxis::Z => self.z = steps_per_um,
+        }
+    }
+}
+
+/// Unit of the UI and calibration of the stage.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnitConfig {
+    pub unit: Unit,
+    pub steps_per_um: AxisScale,
+    /// Size of a camera pixel on the sample, depends on the objective. Needed
+    /// to calibrate X and Y from the image shift.
+    pub um_per_pixel: Option<f64>,
+}
+
+impl UnitConfig {
+    /// Steps per µm of `axis`, `None` while positions are shown in steps or
+    /// the axis is not calibrated.
+    fn scale(&self, axis: OpenflexureAxis) -> Option<f64> {
+        match self.unit {
+            Unit::Steps => None,
+            Unit::Micrometre | Unit::Millimetre => self.steps_per_um.axis(axis),
+        }
+    }
+
+    /// Whether distances of `axis` are shown and entered in µm or mm.
+    pub fn is_physical(&self, axis: OpenflexureAxis) -> bool {
+        self.scale(axis).is_some()
+    }
+
+    /// Position of `axis` in µm, `None` if it is shown in steps.
+    pub fn to_um(&self, axis: OpenflexureAxis, steps: i64) -> Option<f64> {
+        self.scale(axis).map(|scale| steps as f64 / scale)
+    }
+
+    /// Formats a position of `axis` in the configured unit. Axes which are
+    /// not calibrated are shown in steps.
+    pub fn format(&self, axis: OpenflexureAxis, steps: i64) -> (String, &'static str) {
+        let Some(um) = self.to_um(axis, steps) else {
+            return (steps.to_string(), Unit::Steps.symbol());
+        };
+        match self.unit {
+            Unit::Millimetre => (format!("{:.3}", um / 1000.0), self.unit.symbol()),
+            _ => (format!("{:.1}", um), self.unit.symbol()),
+        }
+    }
+
+    /// Like [`UnitConfig::format`], with the unit unless positions are shown
+    /// in steps, e.g. `12.5 µm`.
+    pub fn format_with_unit(&self, axis: OpenflexureAxis, steps: i64) -> String {
+        let (value, unit) = self.format(axis, steps);
+        match self.unit {
+            Unit::Steps => value,
+            _ => format!("{} {}", value, unit),
+        }
+    }
+
+    /// Steps of a distance or position of `axis` in µm. Axes which are shown
+    /// in steps take the value as steps.
+    pub fn um_to_steps(&self, axis: OpenflexureAxis, um: i64) -> i64 {
+        match self.scale(axis) {
+            Some(scale) => (um as f64 * scale).round() as i64,
+            None => um,
+        }
+    }
+}
+
+/// Steps the stage moves per pixel the image shifts, along X and Y.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepsPerPixel {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl StepsPerPixel {
+    /// Reads the steps per pixel from the matrix `m` of the camera stage
+    /// mapping, which maps a displacement in the image to a displacement of
+    /// the stage. A move of the X axis shifts the image by `m⁻¹ · (1, 0)`.
+    pub fn from_image_to_stage(m: [[f64; 2]; 2]) -> Option<Self> {
+        let [[a, b], [c, d]] = m;
+        let det = (a * d - b * c).abs();
+        let x = det / c.hypot(d);
+        let y = det / a.hypot(b);
+        (x.is_normal() && y.is_normal()).then_some(Self { x, y })
+    }
+
+    /// Steps per µm of X and Y for a camera pixel of `um_per_pixel`.
+    pub fn steps_per_um(self, um_per_pixel: f64) -> (f64, f64) {
+        (self.x / um_per_pixel, self.y / um_per_pixel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(unit: Unit) -> UnitConfig {
+        UnitConfig {
+            unit,
+            steps_per_um: AxisScale {
+                x: Some(12.5),
+                y: None,
+                z: Some(40.0),
+            },
+            um_per_pixel: None,
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let config = config(Unit::Micrometre);
+        assert_eq!(
+            config.format(OpenflexureAxis::X, 1250),
+            ("100.0".to_string(), "µm")
+        );
+        // not calibrated
+        assert_eq!(
+            config.format(OpenflexureAxis::Y, 1250),
+            ("1250".to_string(), "st")
+        );
+
+        let config = UnitConfig {
+            unit: Unit::Millimetre,
+            ..config
+        };
+        assert_eq!(
+            config.format(OpenflexureAxis::Z, -60_000),
+            ("-1.500".to_string(), "mm")
+        );
+        assert!(!UnitConfig::default().is_physical(OpenflexureAxis::X));
+    }
+
+    #[test]
+    fn test_um_to_steps() {
+        let config = config(Unit::Micrometre);
+        assert_eq!(config.um_to_steps(OpenflexureAxis::X, 10), 125);
+        assert_eq!(config.um_to_steps(OpenflexureAxis::X, -2), -25);
+        assert_eq!(config.um_to_steps(OpenflexureAxis::Y, 10), 10);
+        assert_eq!(config.to_um(OpenflexureAxis::Z, 400), Some(10.0));
+        assert_eq!(config.format_with_unit(OpenflexureAxis::Y, 1250), "1250 st");
+
+        // the calibration is only used while positions are shown in µm or mm
+        let config = UnitConfig {
+            unit: Unit::Steps,
+            ..config
+        };
+        assert_eq!(config.um_to_steps(OpenflexureAxis::X, 10), 10);
+        assert_eq!(config.to_um(OpenflexureAxis::X, 10), None);
+        assert_eq!(config.format_with_unit(OpenflexureAxis::X, 1250), "1250");
+    }
+
+    #[test]
+    fn test_steps_per_pixel() {
+        // stage and camera axes swapped, 2 steps per pixel along X and 3 along Y
+        let steps = StepsPerPixel::from_image_to_stage([[0.0, 2.0], [-3.0, 0.0]]).unwrap();
+        assert_eq!(steps, StepsPerPixel { x: 2.0, y: 3.0 });
+        assert_eq!(steps.steps_per_um(0.5), (4.0, 6.0));
+
+        let rotated = StepsPerPixel::from_image_to_stage([[1.0, -1.0], [1.0, 1.0]]).unwrap();
+        assert!((rotated.x - 2f64.sqrt()).abs() < 1e-9);
+        assert!(StepsPerPixel::from_image_to_stage([[1.0, 2.0], [2.0, 4.0]]).is_none());
+    }
+}
@@ -4,6 +4,7 @@ use embedded_hal::digital::OutputPin;
 use super::DataFormat;
 use super::DisplayError;
 use super::DisplaySize;
+use super::PanelHealth;
 use super::ReadWriteDataCommand;
 
 type Result<T = (), E = DisplayError> = core::result::Result<T, E>;
@@ -16,6 +17,20 @@ impl DisplaySize for DisplaySize320x240 {
     const HEIGHT: usize = 240;
 }
 
+/// Memory access control of the display, set to landscape mode.
+///
+/// - `0x40 | 0x08` => Portrait
+/// - `0x20 | 0x08` => Landscape
+/// - `0x80 | 0x08` => Portrait flipped
+/// - `0x40 | 0x80 | 0x20 | 0x08` => Landscape flipped
+const MEMORY_ACCESS_CONTROL: u8 = 0x40 | 0x80 | 0x20 | 0x08;
+
+/// Sleep out flag in the second byte of the display status.
+const STATUS_SLEEP_OUT: u8 = 0x02;
+
+/// Display on flag in the third byte of the display status.
+const STATUS_DISPLAY_ON: u8 = 0x04;
+
 /// Enum indicating the on/off state of display modes like sleep or display power.
 pub enum ModeState {
     /// Mode is active.
@@ -69,47 +84,43 @@ where
             width: SIZE::WIDTH,
             height: SIZE::HEIGHT,
         };
+        ili9341.init(delay)?;
+
+        Ok(ili9341)
+    }
 
+    /// Resets the display and runs the initialization sequence.
+    fn init<DELAY>(&mut self, delay: &mut DELAY) -> Result
+    where
+        DELAY: DelayNs,
+    {
         // Hardware reset by holding reset low for at least 10us
-        ili9341.reset.set_low().map_err(|_| DisplayError::RSError)?;
+        self.reset.set_low().map_err(|_| DisplayError::RSError)?;
         delay.delay_ms(1);
 
         // Set high for normal operation
-        ili9341
-            .reset
-            .set_high()
-            .map_err(|_| DisplayError::RSError)?;
+        self.reset.set_high().map_err(|_| DisplayError::RSError)?;
 
         // Wait 5ms after reset before sending commands
         delay.delay_ms(5);
 
         // Do software reset
-        ili9341.command(Command::SoftwareReset, None)?;
+        self.command(Command::SoftwareReset, None)?;
 
         // Wait 120ms before sending Sleep Out
         delay.delay_ms(120);
 
-        // Set display to landscape mode
-        // 0x40 | 0x08 => Portrait
-        // 0x20 | 0x08 => Landscape
-        // 0x80 | 0x08 => Portrait flipped
-        // 0x40 | 0x80 | 0x20 | 0x08 => Landscape flipped
-        ili9341.command(
-            Command::MemoryAccessControl,
-            Some(&[0x40 | 0x80 | 0x20 | 0x08]),
-        )?;
+        self.command(Command::MemoryAccessControl, Some(&[MEMORY_ACCESS_CONTROL]))?;
 
         // Set pixel format to 16 bits per pixel
-        ili9341.command(Command::PixelFormatSet, Some(&[0x55]))?;
+        self.command(Command::PixelFormatSet, Some(&[0x55]))?;
 
-        ili9341.sleep_mode(ModeState::Off)?;
+        self.sleep_mode(ModeState::Off)?;
 
         // Wait 5ms after Sleep Out before sending commands
         delay.delay_ms(5);
 
-        ili9341.display_mode(ModeState::On)?;
-
-        Ok(ili9341)
+        self.display_mode(ModeState::On)
     }
 
     /// Sends a command followed by optional arguments to the display.
@@ -214,6 +225,15 @@ where
         Ok(buf)
     }
 
+    /// Reads the memory access control register (MADCTL), which holds the
+    /// orientation of the display.
+    pub fn memory_access_control(&mut self) -> Result<u8> {
+        // The first byte of the response is a dummy byte
+        let mut buf = [0u8; 2];
+        self.read(Command::ReadMemoryAccessControl, &mut buf)?;
+        Ok(buf[1])
+    }
+
     /// Fills the entire display with a single RGB565 color.
     pub fn clear_screen(&mut self, color: u16) -> Result {
         let color = core::iter::repeat_n(color, self.width * self.height);
@@ -221,6 +241,27 @@ where
     }
 }
 
+impl<IFACE, RESET> PanelHealth for Ili9341<IFACE, RESET>
+where
+    IFACE: ReadWriteDataCommand,
+    RESET: OutputPin,
+{
+    /// The display is healthy if it still uses the configured orientation and
+    /// reports to be awake and switched on.
+    fn is_healthy(&mut self) -> Result<bool> {
+        let madctl = self.memory_access_control()?;
+        let status = self.status()?;
+
+        Ok(madctl == MEMORY_ACCESS_CONTROL
+            && status[2] & STATUS_SLEEP_OUT != 0
+            && status[3] & STATUS_DISPLAY_ON != 0)
+    }
+
+    fn reinit(&mut self, mut delay: &mut dyn DelayNs) -> Result {
+        self.init(&mut delay)
+    }
+}
+
 impl<IFACE, RESET> Ili9341<IFACE, RESET> {
     /// Returns the current width of the display in pixels.
     pub fn width(&self) -> usize {
@@ -247,6 +288,7 @@ fn pack_coords(start: u16, end: u16) -> [u8; 4] {
 enum Command {
     SoftwareReset = 0x01,
     StatusInfo = 0x09,
+    ReadMemoryAccessControl = 0x0b,
     MemoryAccessControl = 0x36,
     PixelFormatSet = 0x3a,
     SleepModeOn = 0x10,
@@ -301,7 +343,7 @@ mod tests {
 
     impl Error for DummyError {
         fn kind(&self) -> ErrorKind {
-            return ErrorKind::Other;
+            ErrorKind::Other
         }
     }
 
@@ -326,6 +368,8 @@ mod tests {
     struct DummyInterface {
         pub commands: RefCell<Vec<u8>>,
         pub data: RefCell<Vec<u8>>,
+        /// Responses to read commands, other reads are filled with a dummy value
+        pub registers: Vec<(u8, Vec<u8>)>,
     }
 
     impl ReadWriteDataCommand for DummyInterface {
@@ -354,8 +398,15 @@ mod tests {
             Ok(())
         }
 
-        fn read_data(&mut self, _cmd: DataFormat<'_>, buf: &mut [u8]) -> Result<(), DisplayError> {
-            buf.fill(0xAB); // dummy value
+        fn read_data(&mut self, cmd: DataFormat<'_>, buf: &mut [u8]) -> Result<(), DisplayError> {
+            let response = match cmd {
+                DataFormat::U8([cmd]) => self.registers.iter().find(|(reg, _)| reg == cmd),
+                _ => None,
+            };
+            match response {
+                Some((_, bytes)) => buf.copy_from_slice(bytes),
+                None => buf.fill(0xAB), // dummy value
+            }
             Ok(())
         }
     }
@@ -406,7 +457,52 @@ mod tests {
         let binding = iface.borrow();
         let data = binding.data.borrow();
 
-        let expected_bytes = vec![0x12, 0x34, 0x12, 0x34, 0x12, 0x34, 0x12, 0x34];
+        let expected_bytes = [0x12, 0x34, 0x12, 0x34, 0x12, 0x34, 0x12, 0x34];
         assert_eq!(&data[data.len() - 8..], &expected_bytes[..]);
     }
+
+    #[test]
+    fn test_health_check() {
+        let iface = DummyInterface {
+            registers: vec![
+                (0x0b, vec![0x00, MEMORY_ACCESS_CONTROL]),
+                (
+                    0x09,
+                    vec![0x00, 0x00, STATUS_SLEEP_OUT, STATUS_DISPLAY_ON, 0x00],
+                ),
+            ],
+            ..Default::default()
+        };
+        let mut display =
+            Ili9341::new(iface, DummyPin::default(), &mut DummyDelay, DummySize).unwrap();
+
+        assert!(display.is_healthy().unwrap());
+
+        // a display after a power glitch comes up in its reset state
+        display.interface.registers = vec![(0x0b, vec![0x00, 0x00])];
+        assert!(!display.is_healthy().unwrap());
+    }
+
+    #[test]
+    fn test_reinit_restores_configuration() {
+        let iface = Rc::new(RefCell::new(DummyInterface::default()));
+        let mut display = Ili9341::new(
+            iface.clone(),
+            DummyPin::default(),
+            &mut DummyDelay,
+            DummySize,
+        )
+        .unwrap();
+        iface.borrow().commands.borrow_mut().clear();
+
+        display.reinit(&mut DummyDelay).unwrap();
+
+        let binding = iface.borrow();
+        let commands = binding.commands.borrow();
+        assert_eq!(
+            commands.as_slice(),
+            &[0x01, 0x36, 0x3a, 0x11, 0x29],
+            "reset, MADCTL, pixel format, sleep out, display on"
+        );
+    }
 }
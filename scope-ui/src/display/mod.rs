@@ -1,6 +1,20 @@
+use embedded_hal::delay::DelayNs;
+
 pub mod graphics_core;
 pub mod ili9341;
 
+/// Displays which can detect that they lost their configuration, e.g. after
+/// an ESD event or a loose ribbon cable, and recover from it.
+pub trait PanelHealth {
+    /// Reads back the state of the display controller and compares it with
+    /// the configured state.
+    fn is_healthy(&mut self) -> Result<bool, DisplayError>;
+
+    /// Resets the display and runs the initialization sequence again. The
+    /// content has to be redrawn afterwards.
+    fn reinit(&mut self, delay: &mut dyn DelayNs) -> Result<(), DisplayError>;
+}
+
 /// Trait for display interfaces that support both command and data transmission,
 /// including optional read-back functionality for supported controllers.
 ///
@@ -1,6 +1,6 @@
 pub mod rotary_encoder;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputEvent {
     Up,
     Down,
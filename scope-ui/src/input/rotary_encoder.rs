@@ -2,6 +2,7 @@ use std::time::{Duration, Instant};
 
 use embedded_hal::digital::InputPin;
 use log::debug;
+use quadrature::{Decoder, Direction, StepMode};
 
 use super::{InputEvent, MenuInput};
 
@@ -9,7 +10,8 @@ pub struct RotaryEncoder<DT, CLK, SW> {
     dt: DT,
     clk: CLK,
     sw: SW,
-    pin_state: [u8; 3],
+    decoder: Decoder,
+    sw_state: u8,
     last_click_time: Instant,
     min_click_interval: Duration,
 }
@@ -25,15 +27,20 @@ where
             dt,
             clk,
             sw,
-            pin_state: [0xFF; 3],
+            decoder: Decoder::new(StepMode::Full),
+            sw_state: 0xFF,
             last_click_time: Instant::now(),
             min_click_interval: Duration::from_millis(700),
         }
     }
+
+    /// Sets the number of signal transitions per detent of the encoder.
+    pub fn with_step_mode(mut self, mode: StepMode) -> Self {
+        self.decoder = Decoder::new(mode);
+        self
+    }
 }
 
-const PIN_MASK: u8 = 0x03;
-const PIN_EDGE: u8 = 0x02;
 const DEBOUNCE_MASK: u8 = 0x0f;
 
 impl<DT, CLK, SW> MenuInput for RotaryEncoder<DT, CLK, SW>
@@ -47,26 +54,22 @@ where
         let clk_value = self.clk.is_high().unwrap_or_default();
         let sw_value = self.sw.is_low().unwrap_or_default();
 
-        self.pin_state[0] = (self.pin_state[0] << 1) | dt_value as u8;
-        self.pin_state[1] = (self.pin_state[1] << 1) | clk_value as u8;
-
         let sw_bit = if sw_value { 0 } else { 1 };
-        self.pin_state[2] = (self.pin_state[2] << 1) | sw_bit;
-
-        let a = self.pin_state[0] & PIN_MASK;
-        let b = self.pin_state[1] & PIN_MASK;
-        let sw = self.pin_state[2] & DEBOUNCE_MASK;
+        self.sw_state = (self.sw_state << 1) | sw_bit;
+        let sw = self.sw_state & DEBOUNCE_MASK;
 
         let now = Instant::now();
-        let mut event = None;
-
-        if a == PIN_EDGE && b == 0x00 {
-            debug!("rotary encoder down, a: 0x{:02x}, b: 0x{:02x}", a, b);
-            event = Some(InputEvent::Down);
-        } else if b == PIN_EDGE && a == 0x00 {
-            debug!("rotary encoder up, a: 0x{:02x}, b: 0x{:02x}", a, b);
-            event = Some(InputEvent::Up);
-        }
+        let mut event = match self.decoder.update(dt_value, clk_value) {
+            Some(Direction::Clockwise) => {
+                debug!("rotary encoder up");
+                Some(InputEvent::Up)
+            }
+            Some(Direction::CounterClockwise) => {
+                debug!("rotary encoder down");
+                Some(InputEvent::Down)
+            }
+            None => None,
+        };
 
         if sw == 0x00 && now.duration_since(self.last_click_time) >= self.min_click_interval {
             debug!("rotary encoder button click");
@@ -77,3 +80,35 @@ where
         event
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quadrature::mock::{MockClock, Script, ScriptedPin, Waveform};
+
+    use super::*;
+
+    #[test]
+    fn test_half_step_turns() {
+        let poll_interval = Duration::from_micros(100);
+        let clock = MockClock::new();
+        let (dt, clk) = Waveform::new(StepMode::Half)
+            .with_bounce(2, Duration::from_micros(20))
+            .scripts(&[-1, 3]);
+        let end = dt.end().max(clk.end());
+        let mut encoder = RotaryEncoder::new(
+            ScriptedPin::new(dt, &clock),
+            ScriptedPin::new(clk, &clock),
+            ScriptedPin::new(Script::new(true), &clock),
+        )
+        .with_step_mode(StepMode::Half);
+
+        let mut events = Vec::new();
+        while clock.now() <= end + poll_interval {
+            events.extend(encoder.poll());
+            clock.advance(poll_interval);
+        }
+
+        use InputEvent::*;
+        assert_eq!(events, [Down, Up, Up, Up]);
+    }
+}
@@ -28,7 +28,7 @@ where
             .write(slice.as_byte_slice())
             .map_err(|_| DisplayError::BusWriteError),
         DataFormat::U16LE(slice) => {
-            for v in slice.as_mut() {
+            for v in slice.iter_mut() {
                 *v = v.to_le();
             }
 
@@ -36,7 +36,7 @@ where
                 .map_err(|_| DisplayError::BusWriteError)
         }
         DataFormat::U16BE(slice) => {
-            for v in slice.as_mut() {
+            for v in slice.iter_mut() {
                 *v = v.to_be();
             }
 
@@ -16,9 +16,6 @@ const ROTARY_CLK: u8 = 17;
 const ROTARY_DT: u8 = 18;
 const ROTARY_SW: u8 = 27;
 
-pub const DISP_WIDTH: u32 = 160;
-pub const DISP_HEIGHT: u32 = 128;
-
 fn main() {
     env_logger::init();
 
@@ -35,7 +32,7 @@ fn main() {
     let rotary_sw = gpio.get(ROTARY_SW).expect("Invalid SW pin").into_input();
 
     let mut input = RotaryEncoder::new(rotary_clk, rotary_dt, rotary_sw);
-    let mut menu = ScopeMenu;
+    let mut menu = ScopeMenu::new(Delay);
     menu.run(&mut display, &mut input);
 }
 
@@ -1,10 +1,15 @@
-use std::{fmt::Debug, process::exit, time::Duration};
+use std::{
+    fmt::Debug,
+    process::exit,
+    time::{Duration, Instant},
+};
 
 use embedded_graphics::{
     mono_font::ascii::{FONT_10X20, FONT_8X13},
     pixelcolor::Rgb565,
     prelude::*,
 };
+use embedded_hal::delay::DelayNs;
 use embedded_menu::{
     interaction::{programmed::ProgrammedAdapter, Action, Interaction, Navigation},
     items::MenuItem,
@@ -12,10 +17,13 @@ use embedded_menu::{
     theme::Theme,
     Menu, MenuState, MenuStyle,
 };
-use log::{debug, error};
+use log::{debug, error, info, warn};
 use position::Position;
 
-use crate::input::{InputEvent, MenuInput};
+use crate::{
+    display::PanelHealth,
+    input::{InputEvent, MenuInput},
+};
 
 mod position;
 
@@ -102,21 +110,45 @@ impl Default for MenuData {
     }
 }
 
-pub struct ScopeMenu;
+/// Interval between two health checks of the display
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct ScopeMenu<DELAY> {
+    delay: DELAY,
+    recoveries: u32,
+}
+
+impl<DELAY> ScopeMenu<DELAY>
+where
+    DELAY: DelayNs,
+{
+    /// Creates the menu. `delay` is used to re-initialize the display after it
+    /// failed a health check.
+    pub fn new(delay: DELAY) -> Self {
+        Self {
+            delay,
+            recoveries: 0,
+        }
+    }
 
-impl ScopeMenu {
     pub fn run<D, I>(&mut self, display: &mut D, input: &mut I)
     where
-        D: DrawTarget<Color = Rgb565, Error: Debug>,
+        D: DrawTarget<Color = Rgb565, Error: Debug> + PanelHealth,
         I: MenuInput,
     {
         let mut state: MenuState<ProgrammedAdapter<MenuEvent>, StaticPosition, Line> =
             Default::default();
         let mut data = MenuData::default();
+        let mut last_health_check = Instant::now();
 
         try_clear_display(display);
 
         loop {
+            if last_health_check.elapsed() >= HEALTH_CHECK_INTERVAL {
+                last_health_check = Instant::now();
+                self.check_display(display);
+            }
+
             match data.current_view {
                 MenuView::MainMenu => main_menu(display, input, &mut state, &mut data),
                 MenuView::Control => control_menu(display, input, &mut state, &mut data),
@@ -129,6 +161,30 @@ impl ScopeMenu {
             std::thread::sleep(Duration::from_micros(1000));
         }
     }
+
+    /// Re-initializes the display if it lost its configuration. The current
+    /// view is redrawn with the next iteration of the menu loop.
+    fn check_display<D>(&mut self, display: &mut D)
+    where
+        D: DrawTarget<Color = Rgb565, Error: Debug> + PanelHealth,
+    {
+        match display.is_healthy() {
+            Ok(true) => return,
+            Ok(false) => warn!("display lost its configuration, re-initializing"),
+            Err(e) => {
+                warn!("failed to read display state: {:?}", e);
+                return;
+            }
+        }
+
+        if let Err(e) = display.reinit(&mut self.delay) {
+            error!("failed to re-initialize display: {:?}", e);
+            return;
+        }
+        self.recoveries += 1;
+        info!("display recovered ({} recoveries so far)", self.recoveries);
+        try_clear_display(display);
+    }
 }
 
 fn try_clear_display<D: DrawTarget<Color = Rgb565>>(display: &mut D) {